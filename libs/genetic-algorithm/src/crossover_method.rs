@@ -30,6 +30,6 @@ impl CrossoverMethod for UniformCrossover {
             .zip(parent_b.iter())
             .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
             .collect();
-        return Chromosome { genes };
+        Chromosome { genes }
     }
 }
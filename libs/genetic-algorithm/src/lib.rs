@@ -22,6 +22,35 @@ where
     selection_method: S,
     crossover_method: C,
     mutation_method: M,
+    elitism: Elitism,
+}
+
+/// How many of the fittest individuals [`GeneticAlgorithm::evolve`] carries
+/// over unchanged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Elitism {
+    Count(usize),
+    /// Share of the population, rounded to the nearest individual.
+    Fraction(f32),
+}
+
+impl Default for Elitism {
+    fn default() -> Self {
+        Elitism::Count(0)
+    }
+}
+
+impl Elitism {
+    /// Number of elites in a population of `population` individuals.
+    pub fn count(&self, population: usize) -> usize {
+        let count = match *self {
+            Elitism::Count(count) => count,
+            Elitism::Fraction(fraction) => {
+                (fraction.clamp(0.0, 1.0) * population as f32).round() as usize
+            }
+        };
+        count.min(population)
+    }
 }
 
 impl<S, C, M> GeneticAlgorithm<S, C, M>
@@ -35,37 +64,67 @@ where
             selection_method,
             crossover_method,
             mutation_method,
+            elitism: Elitism::default(),
         }
     }
 
+    pub fn with_elitism(mut self, elitism: Elitism) -> Self {
+        self.elitism = elitism;
+        self
+    }
+
+    pub fn elitism(&self) -> Elitism {
+        self.elitism
+    }
+
+    pub fn set_elitism(&mut self, elitism: Elitism) {
+        self.elitism = elitism;
+    }
+
+    /// The next generation, as large as `population`: its elites unchanged,
+    /// fittest first, then children of selected parents.
     pub fn evolve<I>(&self, rng: &mut dyn RngCore, population: &[I]) -> Vec<I>
     where
         I: Individual,
     {
         assert!(!population.is_empty());
-        (0..population.len())
-            .map(|_| {
-                let parent_a = match self.selection_method.select(rng, population) {
-                    Ok(i) => i.chromosome(),
-                    Err(e) => {
-                        warn!("{}", e);
-                        population[0].chromosome()
-                    }
-                };
-                let parent_b = match self.selection_method.select(rng, population) {
-                    Ok(i) => i.chromosome(),
-                    Err(e) => {
-                        warn!("{}", e);
-                        population[1].chromosome()
-                    }
-                };
+        let elites = self.elitism.count(population.len());
+        let children = population.len() - elites;
+
+        let mut fittest: Vec<&I> = population.iter().collect();
+        fittest.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+        let elites = fittest
+            .into_iter()
+            .take(elites)
+            .map(|elite| I::from_chromosome(elite.chromosome().clone()));
+
+        let parents = match self
+            .selection_method
+            .select_many(rng, population, 2 * children)
+        {
+            Ok(parents) => parents,
+            Err(e) => {
+                warn!("{}, picking parents uniformly", e);
+                (0..2 * children)
+                    .map(|_| &population[rng.gen_range(0..population.len())])
+                    .collect()
+            }
+        };
+
+        let children = parents
+            .chunks(2)
+            .map(|parents| {
+                let parent_a = parents[0].chromosome();
+                let parent_b = parents[1].chromosome();
 
                 let mut child = self.crossover_method.crossover(rng, parent_a, parent_b);
 
                 self.mutation_method.mutate(rng, &mut child);
                 I::from_chromosome(child)
             })
-            .collect()
+            .collect::<Vec<_>>();
+
+        elites.chain(children).collect()
     }
 }
 
@@ -75,6 +134,7 @@ pub trait Individual {
     fn from_chromosome(chromosome: Chromosome) -> Self;
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub struct Chromosome {
     genes: Vec<f32>,
 }
@@ -84,6 +144,10 @@ impl Chromosome {
         self.genes.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.genes.is_empty()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &f32> {
         self.genes.iter()
     }
@@ -117,3 +181,121 @@ impl IntoIterator for Chromosome {
         self.genes.into_iter()
     }
 }
+
+#[cfg(test)]
+pub(crate) mod test_fixtures {
+    use crate::{Chromosome, Individual};
+
+    pub(crate) struct TestIndividual {
+        pub(crate) fitness: f32,
+        pub(crate) chromosome: Chromosome,
+    }
+
+    impl Individual for TestIndividual {
+        fn fitness(&self) -> f32 {
+            self.fitness
+        }
+
+        fn chromosome(&self) -> &Chromosome {
+            &self.chromosome
+        }
+
+        fn from_chromosome(chromosome: Chromosome) -> Self {
+            Self {
+                fitness: 0.0,
+                chromosome,
+            }
+        }
+    }
+
+    /// Individuals whose single gene is their fitness.
+    pub(crate) fn population(fitnesses: &[f32]) -> Vec<TestIndividual> {
+        fitnesses
+            .iter()
+            .map(|&fitness| TestIndividual {
+                fitness,
+                chromosome: Chromosome::from_iter([fitness]),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_fixtures::{population, TestIndividual};
+    use super::{
+        crossover_method::UniformCrossover,
+        mutation_method::GaussianMutation,
+        selection_method::{RouletteWheelSelection, StochasticUniversalSampling},
+        Chromosome, Elitism, GeneticAlgorithm, Individual,
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    /// Genes of the next generation of individuals whose single gene is their
+    /// fitness. Every child gene mutates to zero, so only elites keep theirs.
+    fn evolve(elitism: Elitism) -> Vec<f32> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population = population(&[3.0, 0.0, 5.0, 1.0, 4.0, 2.0]);
+        let ga = GeneticAlgorithm::new(
+            RouletteWheelSelection,
+            UniformCrossover::new(),
+            GaussianMutation::new(1.0, 0.0),
+        )
+        .with_elitism(elitism);
+
+        ga.evolve(&mut rng, &population)
+            .iter()
+            .map(|individual| individual.chromosome()[0].abs())
+            .collect()
+    }
+
+    #[test]
+    fn elitism_test() {
+        assert_eq!(evolve(Elitism::default()), vec![0.0; 6]);
+        assert_eq!(
+            evolve(Elitism::Count(2)),
+            vec![5.0, 4.0, 0.0, 0.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            evolve(Elitism::Fraction(0.5)),
+            vec![5.0, 4.0, 3.0, 0.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            evolve(Elitism::Count(10)),
+            vec![5.0, 4.0, 3.0, 2.0, 1.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn failed_selection_test() {
+        // all zero fitnesses cannot be sampled, so parents are drawn uniformly
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let population: Vec<_> = (0..6)
+            .map(|gene| TestIndividual {
+                fitness: 0.0,
+                chromosome: Chromosome::from_iter([gene as f32]),
+            })
+            .collect();
+        let ga = GeneticAlgorithm::new(
+            StochasticUniversalSampling,
+            UniformCrossover::new(),
+            GaussianMutation::new(0.0, 0.0),
+        );
+
+        let genes: Vec<_> = ga
+            .evolve(&mut rng, &population)
+            .iter()
+            .map(|individual| individual.chromosome()[0])
+            .collect();
+        assert!(genes.iter().any(|&gene| gene >= 2.0), "{:?}", genes);
+    }
+
+    #[test]
+    fn elitism_count_test() {
+        assert_eq!(Elitism::Count(3).count(2), 2);
+        assert_eq!(Elitism::Fraction(0.1).count(40), 4);
+        assert_eq!(Elitism::Fraction(0.01).count(40), 0);
+        assert_eq!(Elitism::Fraction(2.0).count(40), 40);
+    }
+}
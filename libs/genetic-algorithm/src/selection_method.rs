@@ -1,5 +1,5 @@
 use std::{fmt::Display, error::Error};
-use rand::seq::SliceRandom;
+use rand::{distributions::WeightedIndex, prelude::Distribution, seq::SliceRandom, Rng};
 
 use super::{Individual, RngCore};
 
@@ -17,6 +17,12 @@ impl Display for SelectionError {
 impl Error for SelectionError {}
 
 impl SelectionError {
+    pub(crate) fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
     pub (crate) fn from_error(e: impl Error) -> Self {
         Self { message: format!("Selection failed to select due to error:\n {}", e) }
     }
@@ -26,6 +32,21 @@ pub trait SelectionMethod {
     fn select<'a, I>(&self, rng: &mut dyn RngCore, population: &'a [I]) -> Result<&'a I, SelectionError>
     where
         I: Individual;
+
+    /// `count` parents picked as one pool, which methods like
+    /// [`StochasticUniversalSampling`] need. By default every parent is an
+    /// independent [`SelectionMethod::select`].
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Result<Vec<&'a I>, SelectionError>
+    where
+        I: Individual,
+    {
+        (0..count).map(|_| self.select(rng, population)).collect()
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -41,4 +62,449 @@ impl SelectionMethod for RouletteWheelSelection {
             Err(e) => Err(SelectionError::from_error(e)),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Draws `size` individuals at random, with replacement, and picks the fittest
+/// of them with probability `p_best`, the runner-up with
+/// `p_best * (1 - p_best)` and so on, the least fit taking what is left.
+///
+/// Only the order of fitnesses matters, so they may be negative, all zero or
+/// on any scale.
+#[derive(Clone, Debug)]
+pub struct TournamentSelection {
+    pub size: usize,
+    pub p_best: f32,
+}
+
+impl Default for TournamentSelection {
+    fn default() -> Self {
+        Self {
+            size: 2,
+            p_best: 1.0,
+        }
+    }
+}
+
+impl SelectionMethod for TournamentSelection {
+    fn select<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+    ) -> Result<&'a I, SelectionError>
+    where
+        I: Individual,
+    {
+        if population.is_empty() {
+            return Err(SelectionError::new(
+                "cannot hold a tournament in an empty population",
+            ));
+        }
+        if self.size == 0 {
+            return Err(SelectionError::new(
+                "a tournament needs at least one entrant",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.p_best) {
+            return Err(SelectionError::new(format!(
+                "p_best {} is not a probability",
+                self.p_best
+            )));
+        }
+
+        let mut entrants: Vec<&I> = (0..self.size)
+            .map(|_| &population[rng.gen_range(0..population.len())])
+            .collect();
+        entrants.sort_by(|a, b| b.fitness().total_cmp(&a.fitness()));
+
+        let last = entrants.len() - 1;
+        let place = (0..last)
+            .find(|_| rng.gen_bool(self.p_best as f64))
+            .unwrap_or(last);
+        Ok(entrants[place])
+    }
+}
+
+/// How steeply [`RankSelection`] favours the fitter ranks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ranking {
+    /// Weights grow linearly from `2 - pressure` for the least fit to
+    /// `pressure` for the fittest, so `pressure` within `[1, 2]` goes from a
+    /// uniform draw to a least fit that is never picked.
+    Linear { pressure: f32 },
+    /// The fittest has weight `1`, the runner-up `base`, the next `base^2`
+    /// and so on, for `base` within `(0, 1]`.
+    Exponential { base: f32 },
+}
+
+/// Roulette wheel over ranks rather than fitnesses, so it works with any
+/// fitness, negative or all zero, and a single outlier cannot take over.
+#[derive(Clone, Debug)]
+pub struct RankSelection {
+    pub ranking: Ranking,
+}
+
+impl Default for RankSelection {
+    fn default() -> Self {
+        Self {
+            ranking: Ranking::Linear { pressure: 1.5 },
+        }
+    }
+}
+
+impl RankSelection {
+    fn distribution<I>(&self, population: &[I]) -> Result<WeightedIndex<f32>, SelectionError>
+    where
+        I: Individual,
+    {
+        let valid = match self.ranking {
+            Ranking::Linear { pressure } => (1.0..=2.0).contains(&pressure),
+            Ranking::Exponential { base } => base > 0.0 && base <= 1.0,
+        };
+        if !valid {
+            return Err(SelectionError::new(format!(
+                "{:?} is out of range",
+                self.ranking
+            )));
+        }
+        // the only rank may well weigh nothing, as the least fit
+        if population.len() == 1 {
+            return WeightedIndex::new([1.0]).map_err(SelectionError::from_error);
+        }
+
+        // least fit first
+        let mut order: Vec<usize> = (0..population.len()).collect();
+        order.sort_by(|&a, &b| population[a].fitness().total_cmp(&population[b].fitness()));
+
+        let worst = population.len().saturating_sub(1) as f32;
+        let mut weights = vec![0.0; population.len()];
+        for (rank, &index) in order.iter().enumerate() {
+            let rank = rank as f32;
+            weights[index] = match self.ranking {
+                Ranking::Linear { pressure } => {
+                    (2.0 - pressure) + 2.0 * (pressure - 1.0) * rank / worst
+                }
+                Ranking::Exponential { base } => base.powf(worst - rank),
+            };
+        }
+        WeightedIndex::new(weights).map_err(SelectionError::from_error)
+    }
+}
+
+impl SelectionMethod for RankSelection {
+    fn select<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+    ) -> Result<&'a I, SelectionError>
+    where
+        I: Individual,
+    {
+        let distribution = self.distribution(population)?;
+        Ok(&population[distribution.sample(rng)])
+    }
+
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Result<Vec<&'a I>, SelectionError>
+    where
+        I: Individual,
+    {
+        let distribution = self.distribution(population)?;
+        Ok((0..count)
+            .map(|_| &population[distribution.sample(rng)])
+            .collect())
+    }
+}
+
+/// Roulette wheel spun once with `count` evenly spaced pointers, so every
+/// individual is picked within one of as often as its fitness share says.
+/// Fitnesses must not be negative nor all zero.
+#[derive(Clone, Debug, Default)]
+pub struct StochasticUniversalSampling;
+
+impl SelectionMethod for StochasticUniversalSampling {
+    fn select<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+    ) -> Result<&'a I, SelectionError>
+    where
+        I: Individual,
+    {
+        Ok(self.select_many(rng, population, 1)?[0])
+    }
+
+    /// The pool is shuffled, since parents next to each other on the wheel
+    /// would otherwise be paired up.
+    fn select_many<'a, I>(
+        &self,
+        rng: &mut dyn RngCore,
+        population: &'a [I],
+        count: usize,
+    ) -> Result<Vec<&'a I>, SelectionError>
+    where
+        I: Individual,
+    {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let invalid = |fitness: f32| fitness.is_nan() || fitness < 0.0;
+        if let Some(individual) = population
+            .iter()
+            .find(|individual| invalid(individual.fitness()))
+        {
+            return Err(SelectionError::new(format!(
+                "fitness {} is not a valid weight",
+                individual.fitness()
+            )));
+        }
+        let total: f32 = population.iter().map(Individual::fitness).sum();
+        if !(total > 0.0 && total.is_finite()) {
+            return Err(SelectionError::new(format!(
+                "total fitness {} cannot be sampled",
+                total
+            )));
+        }
+
+        let spacing = total / count as f32;
+        let start = rng.gen_range(0.0..spacing);
+        let mut pool = Vec::with_capacity(count);
+        let mut individuals = population.iter();
+        let mut current = individuals.next().unwrap();
+        let mut reach = current.fitness();
+        for pointer in (0..count).map(|i| start + i as f32 * spacing) {
+            while pointer >= reach {
+                match individuals.next() {
+                    Some(next) => {
+                        current = next;
+                        reach += current.fitness();
+                    }
+                    // rounding left the last pointer just past the wheel
+                    None => break,
+                }
+            }
+            pool.push(current);
+        }
+        pool.shuffle(rng);
+        Ok(pool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        RankSelection, Ranking, SelectionMethod, StochasticUniversalSampling, TournamentSelection,
+    };
+    use crate::test_fixtures::{population, TestIndividual};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    const DRAWS: usize = 16_000;
+
+    /// How often each individual was selected out of `DRAWS`.
+    fn histogram(method: &impl SelectionMethod, population: &[TestIndividual]) -> Vec<usize> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut counts = vec![0; population.len()];
+        for _ in 0..DRAWS {
+            let selected = method.select(&mut rng, population).unwrap();
+            let index = population
+                .iter()
+                .position(|individual| std::ptr::eq(individual, selected))
+                .unwrap();
+            counts[index] += 1;
+        }
+        counts
+    }
+
+    /// Every count is within 5% of `DRAWS` of its expected share.
+    fn assert_distribution(counts: &[usize], expected: &[f32]) {
+        for (&count, &expected) in counts.iter().zip(expected) {
+            let share = count as f32 / DRAWS as f32;
+            assert!(
+                (share - expected).abs() < 0.05,
+                "{:?} vs {:?}",
+                counts,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn tournament_favours_the_fittest_test() {
+        // the best of two uniform draws among four is individual i with
+        // probability ((i + 1)^2 - i^2) / 16
+        let method = TournamentSelection {
+            size: 2,
+            p_best: 1.0,
+        };
+        let counts = histogram(&method, &population(&[0.0, 1.0, 2.0, 3.0]));
+
+        assert_distribution(&counts, &[1.0 / 16.0, 3.0 / 16.0, 5.0 / 16.0, 7.0 / 16.0]);
+        assert!(counts.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn tournament_ignores_fitness_scale_test() {
+        let method = TournamentSelection {
+            size: 2,
+            p_best: 1.0,
+        };
+
+        let negative = histogram(&method, &population(&[-30.0, -20.0, -10.0, 0.0]));
+        let positive = histogram(&method, &population(&[1.0, 2.0, 3.0, 4.0]));
+        assert_eq!(negative, positive);
+
+        let zeros = histogram(&method, &population(&[0.0; 4]));
+        assert!(zeros.iter().all(|&count| count > 0));
+    }
+
+    #[test]
+    fn tournament_p_best_test() {
+        // the weaker entrant wins every tournament it enters
+        let method = TournamentSelection {
+            size: 2,
+            p_best: 0.0,
+        };
+        let counts = histogram(&method, &population(&[0.0, 1.0, 2.0, 3.0]));
+        assert_distribution(&counts, &[7.0 / 16.0, 5.0 / 16.0, 3.0 / 16.0, 1.0 / 16.0]);
+
+        // a coin toss between the two entrants is a uniform draw
+        let method = TournamentSelection {
+            size: 2,
+            p_best: 0.5,
+        };
+        let counts = histogram(&method, &population(&[0.0, 1.0, 2.0, 3.0]));
+        assert_distribution(&counts, &[0.25; 4]);
+    }
+
+    #[test]
+    fn tournament_errors_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let individuals = population(&[1.0, 2.0]);
+
+        let empty = TournamentSelection {
+            size: 0,
+            p_best: 1.0,
+        };
+        assert!(empty.select(&mut rng, &individuals).is_err());
+
+        let improbable = TournamentSelection {
+            size: 2,
+            p_best: 1.5,
+        };
+        assert!(improbable.select(&mut rng, &individuals).is_err());
+
+        let method = TournamentSelection::default();
+        assert!(method.select(&mut rng, &population(&[])).is_err());
+    }
+
+    #[test]
+    fn rank_linear_test() {
+        // weights 0, 2/3, 4/3, 2 out of 4, in whatever order the fitnesses are
+        let method = RankSelection {
+            ranking: Ranking::Linear { pressure: 2.0 },
+        };
+        let counts = histogram(&method, &population(&[5.0, -1.0, 0.0, 2.0]));
+        assert_distribution(&counts, &[0.5, 0.0, 1.0 / 6.0, 1.0 / 3.0]);
+        assert_eq!(counts[1], 0);
+
+        let method = RankSelection {
+            ranking: Ranking::Linear { pressure: 1.0 },
+        };
+        let counts = histogram(&method, &population(&[5.0, -1.0, 0.0, 2.0]));
+        assert_distribution(&counts, &[0.25; 4]);
+    }
+
+    #[test]
+    fn rank_exponential_test() {
+        // weights 1/8, 1/4, 1/2, 1 out of 15/8
+        let method = RankSelection {
+            ranking: Ranking::Exponential { base: 0.5 },
+        };
+        let counts = histogram(&method, &population(&[0.0, 0.0, 1.0, 3.0]));
+        assert_distribution(&counts, &[1.0 / 15.0, 2.0 / 15.0, 4.0 / 15.0, 8.0 / 15.0]);
+    }
+
+    #[test]
+    fn rank_errors_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let individuals = population(&[1.0, 2.0]);
+
+        let method = RankSelection {
+            ranking: Ranking::Linear { pressure: 3.0 },
+        };
+        assert!(method.select(&mut rng, &individuals).is_err());
+
+        let method = RankSelection {
+            ranking: Ranking::Exponential { base: 0.0 },
+        };
+        assert!(method.select(&mut rng, &individuals).is_err());
+
+        assert!(RankSelection::default()
+            .select(&mut rng, &population(&[]))
+            .is_err());
+    }
+
+    #[test]
+    fn rank_single_individual_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let individuals = population(&[1.0]);
+
+        let method = RankSelection {
+            ranking: Ranking::Linear { pressure: 2.0 },
+        };
+        assert_eq!(
+            method.select_many(&mut rng, &individuals, 3).unwrap().len(),
+            3
+        );
+    }
+
+    #[test]
+    fn sus_spreads_the_pool_test() {
+        let individuals = population(&[1.0, 0.0, 2.0, 1.0, 4.0]);
+        for seed in 0..20 {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            let pool = StochasticUniversalSampling
+                .select_many(&mut rng, &individuals, 8)
+                .unwrap();
+
+            // the pool holds exactly each individual's share of 8
+            let counts: Vec<_> = individuals
+                .iter()
+                .map(|individual| {
+                    pool.iter()
+                        .filter(|&&selected| std::ptr::eq(individual, selected))
+                        .count()
+                })
+                .collect();
+            assert_eq!(counts, vec![1, 0, 2, 1, 4]);
+        }
+
+        let counts = histogram(&StochasticUniversalSampling, &individuals);
+        assert_distribution(&counts, &[0.125, 0.0, 0.25, 0.125, 0.5]);
+    }
+
+    #[test]
+    fn sus_errors_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        let method = StochasticUniversalSampling;
+        assert!(method.select(&mut rng, &population(&[0.0, 0.0])).is_err());
+        assert!(method.select(&mut rng, &population(&[1.0, -1.0])).is_err());
+        assert!(method.select(&mut rng, &population(&[])).is_err());
+    }
+
+    #[test]
+    fn select_many_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let individuals = population(&[1.0, 2.0, 3.0]);
+
+        let pool = TournamentSelection::default()
+            .select_many(&mut rng, &individuals, 7)
+            .unwrap();
+        assert_eq!(pool.len(), 7);
+    }
+}
@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Float;
+
+/// Nonlinearity applied element-wise to the output of a layer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Activation {
+    Identity,
+    #[default]
+    ReLU,
+    /// ReLU which lets through `slope * x` for negative inputs.
+    LeakyReLU(f32),
+    Tanh,
+    Sigmoid,
+    Softsign,
+}
+
+impl Activation {
+    pub fn apply<T: Float>(&self, x: T) -> T {
+        let one = T::one();
+        match *self {
+            Activation::Identity => x,
+            Activation::ReLU => x.max(T::zero()),
+            Activation::LeakyReLU(slope) => {
+                if x > T::zero() {
+                    x
+                } else {
+                    T::cast_f32(slope) * x
+                }
+            }
+            Activation::Tanh => x.tanh(),
+            Activation::Sigmoid => one / (one + (-x).exp()),
+            Activation::Softsign => x / (one + x.abs()),
+        }
+    }
+
+    /// Derivative of [`Activation::apply`] at `x`.
+    pub fn derivative<T: Float>(&self, x: T) -> T {
+        let one = T::one();
+        match *self {
+            Activation::Identity => one,
+            Activation::ReLU => {
+                if x > T::zero() {
+                    one
+                } else {
+                    T::zero()
+                }
+            }
+            Activation::LeakyReLU(slope) => {
+                if x > T::zero() {
+                    one
+                } else {
+                    T::cast_f32(slope)
+                }
+            }
+            Activation::Tanh => one - x.tanh().powi(2),
+            Activation::Sigmoid => {
+                let y = self.apply(x);
+                y * (one - y)
+            }
+            Activation::Softsign => one / (one + x.abs()).powi(2),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Activation;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(Activation::Identity.apply(-2.0), -2.0);
+        assert_eq!(Activation::ReLU.apply(-2.0), 0.0);
+        assert_eq!(Activation::ReLU.apply(2.0), 2.0);
+        assert_eq!(Activation::LeakyReLU(0.1).apply(-2.0f32), -0.2);
+        assert_eq!(Activation::LeakyReLU(0.1).apply(2.0), 2.0);
+        assert_eq!(Activation::Tanh.apply(0.0), 0.0);
+        assert_eq!(Activation::Sigmoid.apply(0.0), 0.5);
+        assert_eq!(Activation::Softsign.apply(-1.0), -0.5);
+    }
+
+    #[test]
+    fn test_derivative() {
+        let h = 1e-3;
+        for activation in [
+            Activation::Identity,
+            Activation::ReLU,
+            Activation::LeakyReLU(0.1),
+            Activation::Tanh,
+            Activation::Sigmoid,
+            Activation::Softsign,
+        ] {
+            for x in [-1.5f32, -0.3, 0.4, 2.0] {
+                let numeric = (activation.apply(x + h) - activation.apply(x - h)) / (2.0 * h);
+                assert!(
+                    (numeric - activation.derivative(x)).abs() < 1e-2,
+                    "{:?} at {}",
+                    activation,
+                    x
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_signed_outputs() {
+        for activation in [
+            Activation::Identity,
+            Activation::LeakyReLU(0.01),
+            Activation::Tanh,
+            Activation::Softsign,
+        ] {
+            assert!(activation.apply(-1.0) < 0.0, "{:?}", activation);
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Turns a feed-forward [`Network`] into dependency-free Rust source.
+//!
+//! The generated module holds one `const` weight matrix and bias vector per
+//! layer plus `pub fn propagate(input: &[f32; INPUTS]) -> [f32; OUTPUTS]`.
+//! It uses no crates, so it can be dropped into any crate, but `tanh` and
+//! sigmoid layers call `f32::tanh` and `f32::exp`, which need `std`.
+
+use std::fmt::Write;
+
+use crate::{Activation, Network, NetworkError};
+
+impl Network {
+    /// Source of a standalone Rust module computing [`Network::propagate`].
+    /// Only feed-forward layers without skip connections can be emitted;
+    /// pruned connections become zero weights. `NaN` and infinite weights
+    /// have no literal and are rejected, as are such leaky ReLU slopes, which
+    /// are reported at the first weight of their layer.
+    pub fn to_rust(&self) -> Result<String, NetworkError> {
+        let layers = self.feed_forward_chain()?;
+        if let Some((index, value)) = self.weights().enumerate().find(|(_, w)| !w.is_finite()) {
+            return Err(NetworkError::NonFiniteWeight {
+                index,
+                value: value as f64,
+            });
+        }
+        let mut index = 0;
+        for layer in &layers {
+            if let Activation::LeakyReLU(slope) = layer.activation() {
+                if !slope.is_finite() {
+                    return Err(NetworkError::NonFiniteWeight {
+                        index,
+                        value: slope as f64,
+                    });
+                }
+            }
+            index += layer.weights().count();
+        }
+
+        let inputs = self.topology[0].neurons;
+        let outputs = self.topology[self.topology.len() - 1].neurons;
+        let shape: Vec<_> = self
+            .topology
+            .iter()
+            .map(|l| l.neurons.to_string())
+            .collect();
+
+        // writing into a String cannot fail
+        let mut source = String::new();
+        let out = &mut source;
+        writeln!(out, "// Generated by lib-neural-network, do not edit.").unwrap();
+        writeln!(out, "// Layers: {}", shape.join(" -> ")).unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "pub const INPUTS: usize = {};", inputs).unwrap();
+        writeln!(out, "pub const OUTPUTS: usize = {};", outputs).unwrap();
+
+        for (index, layer) in layers.iter().enumerate() {
+            let weights = layer.weight_matrix();
+            let (rows, cols) = weights.shape();
+            writeln!(out).unwrap();
+            writeln!(out, "const W{}: [[f32; {}]; {}] = [", index, cols, rows).unwrap();
+            for row in weights.row_iter() {
+                writeln!(out, "    [{}],", literals(row.iter())).unwrap();
+            }
+            writeln!(out, "];").unwrap();
+            writeln!(
+                out,
+                "const B{}: [f32; {}] = [{}];",
+                index,
+                rows,
+                literals(layer.biases().iter())
+            )
+            .unwrap();
+        }
+
+        writeln!(out).unwrap();
+        writeln!(
+            out,
+            "pub fn propagate(input: &[f32; INPUTS]) -> [f32; OUTPUTS] {{"
+        )
+        .unwrap();
+        for (index, layer) in layers.iter().enumerate() {
+            let input = match index {
+                0 => "input".to_owned(),
+                _ => format!("&hidden{}", index - 1),
+            };
+            let call = format!(
+                "dense({}, &W{}, &B{}, {})",
+                input,
+                index,
+                index,
+                activation(layer.activation())
+            );
+            if index + 1 == layers.len() {
+                writeln!(out, "    {}", call).unwrap();
+            } else {
+                writeln!(out, "    let hidden{} = {};", index, call).unwrap();
+            }
+        }
+        writeln!(out, "}}").unwrap();
+        out.push_str(DENSE);
+
+        Ok(source)
+    }
+}
+
+const DENSE: &str = "
+fn dense<const I: usize, const O: usize>(
+    input: &[f32; I],
+    weights: &[[f32; I]; O],
+    biases: &[f32; O],
+    activation: fn(f32) -> f32,
+) -> [f32; O] {
+    let mut output = [0.0; O];
+    for ((output, row), bias) in output.iter_mut().zip(weights).zip(biases) {
+        let mut sum = 0.0;
+        for (weight, x) in row.iter().zip(input) {
+            sum += weight * x;
+        }
+        *output = activation(sum + bias);
+    }
+    output
+}
+";
+
+/// Comma separated `f32` literals. `Debug` prints the shortest text that
+/// parses back to the same value, so no precision is lost.
+fn literals<'a>(values: impl Iterator<Item = &'a f32>) -> String {
+    values
+        .map(|value| format!("{:?}", value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn activation(activation: Activation) -> String {
+    match activation {
+        Activation::Identity => "|x| x".to_owned(),
+        Activation::ReLU => "|x| x.max(0.0)".to_owned(),
+        Activation::LeakyReLU(slope) => {
+            format!("|x| if x > 0.0 {{ x }} else {{ {:?} * x }}", slope)
+        }
+        Activation::Tanh => "f32::tanh".to_owned(),
+        Activation::Sigmoid => "|x| 1.0 / (1.0 + (-x).exp())".to_owned(),
+        Activation::Softsign => "|x| x / (1.0 + x.abs())".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        Activation, LayerKind, LayerTopology, Loss, Network, NetworkError, Optimizer, Trainer,
+    };
+
+    #[test]
+    fn test_emits_every_layer() {
+        let layers = &[
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::LeakyReLU(0.5),
+                ..Default::default()
+            },
+        ];
+        let network: Network = Network::from_weights(layers, vec![0.25, 1.0, -3.0]);
+
+        let source = network.to_rust().unwrap();
+
+        assert!(source.contains("pub const INPUTS: usize = 2;"));
+        assert!(source.contains("pub const OUTPUTS: usize = 1;"));
+        assert!(source.contains("const W0: [[f32; 2]; 1] = [\n    [1.0, -3.0],\n];"));
+        assert!(source.contains("const B0: [f32; 1] = [0.25];"));
+        assert!(source.contains("dense(input, &W0, &B0, |x| if x > 0.0 { x } else { 0.5 * x })"));
+    }
+
+    #[test]
+    fn test_recurrent_layer_unsupported() {
+        let layers = &[
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                kind: LayerKind::Elman,
+                ..Default::default()
+            },
+        ];
+        let network: Network = Network::from_weights(layers, vec![0.0, 1.0, 1.0]);
+
+        assert_eq!(
+            network.to_rust(),
+            Err(NetworkError::UnsupportedLayer {
+                layer: 1,
+                kind: LayerKind::Elman
+            })
+        );
+    }
+
+    #[test]
+    fn test_non_finite_weight_rejected() {
+        let layers = &[
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                ..Default::default()
+            },
+        ];
+        let mut network: Network = Network::from_weights(layers, vec![0.0, 1.0]);
+        let mut trainer = Trainer::new(Loss::MeanSquaredError, Optimizer::sgd(f32::INFINITY));
+        trainer
+            .train_batch(&mut network, &[(vec![1.0], vec![0.0])])
+            .unwrap();
+
+        assert!(matches!(
+            network.to_rust(),
+            Err(NetworkError::NonFiniteWeight { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_non_finite_slope_rejected() {
+        let layers = &mut [
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Identity,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::LeakyReLU(f32::NAN),
+                ..Default::default()
+            },
+        ];
+        let weights = vec![0.0, 1.0, 0.0, -1.0, 0.5, 1.0, 1.0];
+
+        let network: Network = Network::from_weights(layers, weights.clone());
+        assert!(matches!(
+            network.to_rust(),
+            Err(NetworkError::NonFiniteWeight { index: 4, value }) if value.is_nan()
+        ));
+
+        layers[2].activation = Activation::LeakyReLU(f32::NEG_INFINITY);
+        let network: Network = Network::from_weights(layers, weights);
+        assert_eq!(
+            network.to_rust(),
+            Err(NetworkError::NonFiniteWeight {
+                index: 4,
+                value: f64::NEG_INFINITY
+            })
+        );
+    }
+}
@@ -0,0 +1,367 @@
+use std::iter::once;
+
+use log::debug;
+use nalgebra::{DMatrix, DVector, DVectorSlice, DVectorSliceMut};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::{Activation, Float, Initializer, LayerKind, LayerTopology, NetworkError};
+
+/// How a [`LayerKind::Conv1d`] layer treats kernel taps that fall past either
+/// end of its input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Padding {
+    /// No padding: the kernel only visits positions where it fits entirely,
+    /// giving `(length - kernel) / stride + 1` outputs per channel.
+    Valid,
+    /// Missing inputs read as zero, giving `ceil(length / stride)` outputs
+    /// per channel centred on the input positions.
+    Zeros,
+    /// The input wraps around, giving `ceil(length / stride)` outputs per
+    /// channel. Suited to the eye, whose first and last cells are neighbours
+    /// when it sees all the way around.
+    Circular,
+}
+
+/// Geometry of a convolution between two layers. Values are laid out
+/// channel-major: position `p` of channel `c` is at `c * length + p`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Shape {
+    in_channels: usize,
+    length: usize,
+    channels: usize,
+    kernel: usize,
+    stride: usize,
+    padding: Padding,
+}
+
+impl Shape {
+    /// `output` must be a [`LayerKind::Conv1d`] layer.
+    pub(crate) fn new(input: &LayerTopology, output: &LayerTopology) -> Self {
+        let LayerKind::Conv1d {
+            channels,
+            kernel,
+            stride,
+            padding,
+        } = output.kind
+        else {
+            unreachable!("{:?} is not a convolution", output.kind)
+        };
+        let in_channels = Self::channels(input);
+        Self {
+            in_channels,
+            length: input.neurons / in_channels,
+            channels,
+            kernel,
+            stride,
+            padding,
+        }
+    }
+
+    /// A convolution reads the channels of a previous convolution, anything
+    /// else is a single channel. A previous convolution without channels is
+    /// rejected on its own, so it counts as one here to avoid dividing by zero.
+    fn channels(layer: &LayerTopology) -> usize {
+        match layer.kind {
+            LayerKind::Conv1d { channels, .. } => channels.max(1),
+            _ => 1,
+        }
+    }
+
+    /// Whether the layer fits its input, `None` if the parameters are unusable
+    /// and otherwise how many neurons the layer must have.
+    pub(crate) fn neurons(input: &LayerTopology, output: &LayerTopology) -> Option<usize> {
+        let shape = Self::new(input, output);
+        let usable = shape.channels > 0
+            && shape.kernel > 0
+            && shape.stride > 0
+            && shape.length * shape.in_channels == input.neurons
+            && (shape.padding != Padding::Valid || shape.kernel <= shape.length);
+        if !usable {
+            return None;
+        }
+        shape.channels.checked_mul(shape.out_length())
+    }
+
+    fn out_length(&self) -> usize {
+        match self.padding {
+            Padding::Valid => (self.length - self.kernel) / self.stride + 1,
+            Padding::Zeros | Padding::Circular => (self.length - 1) / self.stride + 1,
+        }
+    }
+
+    /// Input position read by `tap` of the kernel at output position `out`.
+    fn position(&self, out: usize, tap: usize) -> Option<usize> {
+        let start = out * self.stride;
+        match self.padding {
+            Padding::Valid => Some(start + tap),
+            Padding::Zeros => (start + tap)
+                .checked_sub((self.kernel - 1) / 2)
+                .filter(|&position| position < self.length),
+            Padding::Circular => Some(
+                (start + tap + self.length - (self.kernel - 1) / 2 % self.length) % self.length,
+            ),
+        }
+    }
+
+    /// `None` if the count overflows.
+    fn weight_count(&self) -> Option<usize> {
+        self.channels
+            .checked_mul(self.in_channels.checked_mul(self.kernel)?.checked_add(1)?)
+    }
+}
+
+/// 1D convolution sharing one kernel per output channel across every position.
+///
+/// Weights are laid out per output channel as `bias, kernel..`, the kernel
+/// listing every tap of the first input channel, then the second, and so on.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Conv1d<T: Float> {
+    shape: Shape,
+    /// One row per output channel, column `in_channel * kernel + tap`.
+    kernels: DMatrix<T>,
+    biases: DVector<T>,
+    activation: Activation,
+}
+
+impl<T: Float> Conv1d<T> {
+    pub(crate) fn random(
+        shape: Shape,
+        activation: Activation,
+        initializer: Initializer,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        debug!("create new random convolution {:?}", shape);
+        let fan_in = shape.in_channels * shape.kernel;
+        let fan_out = shape.channels * shape.kernel;
+        Self {
+            shape,
+            kernels: DMatrix::from_fn(shape.channels, fan_in, |_, _| {
+                T::cast_f32(initializer.weight(fan_in, fan_out, rng))
+            }),
+            biases: DVector::from_fn(shape.channels, |_, _| T::cast_f32(initializer.bias(rng))),
+            activation,
+        }
+    }
+
+    pub(crate) fn try_from_weights(
+        shape: Shape,
+        activation: Activation,
+        weights: &mut dyn Iterator<Item = T>,
+    ) -> Result<Self, NetworkError> {
+        let expected = shape.weight_count().unwrap_or(usize::MAX);
+        let mut received = 0;
+        let mut next = || {
+            let weight = weights
+                .next()
+                .ok_or(NetworkError::TooFewWeights { expected, received });
+            received += 1;
+            weight
+        };
+
+        let mut layer = Self {
+            shape,
+            kernels: DMatrix::zeros(shape.channels, shape.in_channels * shape.kernel),
+            biases: DVector::zeros(shape.channels),
+            activation,
+        };
+        for row in 0..shape.channels {
+            layer.biases[row] = next()?;
+            for col in 0..layer.kernels.ncols() {
+                layer.kernels[(row, col)] = next()?;
+            }
+        }
+        Ok(layer)
+    }
+
+    pub(crate) fn weight_count(shape: Shape) -> Option<usize> {
+        shape.weight_count()
+    }
+
+    pub(crate) fn weights(&self) -> impl Iterator<Item = T> + '_ {
+        self.kernels
+            .row_iter()
+            .zip(self.biases.iter())
+            .flat_map(|(row, &bias)| once(bias).chain(row.iter().cloned()).collect::<Vec<_>>())
+    }
+
+    pub(crate) fn cast<U: Float>(&self) -> Conv1d<U> {
+        let cast = |x: T| U::from_subset(&x.as_f64());
+        Conv1d {
+            shape: self.shape,
+            kernels: self.kernels.map(cast),
+            biases: self.biases.map(cast),
+            activation: self.activation,
+        }
+    }
+
+    pub(crate) fn propagate_into(
+        &self,
+        inputs: &DVectorSlice<T>,
+        outputs: &mut DVectorSliceMut<T>,
+    ) {
+        let Shape {
+            in_channels,
+            length,
+            kernel,
+            ..
+        } = self.shape;
+        let out_length = self.shape.out_length();
+
+        for (channel, kernels) in self.kernels.row_iter().enumerate() {
+            for out in 0..out_length {
+                let mut sum = self.biases[channel];
+                for tap in 0..kernel {
+                    if let Some(position) = self.shape.position(out, tap) {
+                        for in_channel in 0..in_channels {
+                            sum += kernels[in_channel * kernel + tap]
+                                * inputs[in_channel * length + position];
+                        }
+                    }
+                }
+                outputs[channel * out_length + out] = self.activation.apply(sum);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Conv1d, Padding, Shape};
+    use crate::{Activation, Initializer, LayerKind, LayerTopology};
+    use nalgebra::DVector;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn layers(
+        inputs: usize,
+        channels: usize,
+        kernel: usize,
+        stride: usize,
+        padding: Padding,
+    ) -> (LayerTopology, LayerTopology) {
+        let input = LayerTopology {
+            neurons: inputs,
+            ..Default::default()
+        };
+        let mut output = LayerTopology {
+            kind: LayerKind::Conv1d {
+                channels,
+                kernel,
+                stride,
+                padding,
+            },
+            ..Default::default()
+        };
+        output.neurons = Shape::neurons(&input, &output).unwrap();
+        (input, output)
+    }
+
+    fn propagate(layer: &Conv1d<f32>, inputs: &[f32], neurons: usize) -> Vec<f32> {
+        let inputs = DVector::from_column_slice(inputs);
+        let mut outputs = DVector::zeros(neurons);
+        layer.propagate_into(
+            &inputs.rows(0, inputs.len()),
+            &mut outputs.rows_mut(0, neurons),
+        );
+        outputs.data.into()
+    }
+
+    #[test]
+    fn output_lengths_test() {
+        assert_eq!(layers(9, 1, 3, 1, Padding::Valid).1.neurons, 7);
+        assert_eq!(layers(9, 2, 3, 2, Padding::Valid).1.neurons, 8);
+        assert_eq!(layers(9, 1, 3, 2, Padding::Zeros).1.neurons, 5);
+        assert_eq!(layers(9, 4, 3, 1, Padding::Circular).1.neurons, 36);
+
+        let (input, mut output) = layers(2, 1, 1, 1, Padding::Valid);
+        output.kind = LayerKind::Conv1d {
+            channels: 1,
+            kernel: 3,
+            stride: 1,
+            padding: Padding::Valid,
+        };
+        assert_eq!(Shape::neurons(&input, &output), None);
+    }
+
+    #[test]
+    fn padding_test() {
+        // bias 0, kernel [1, 10, 100] centred on each position
+        let weights = [0.0, 1.0, 10.0, 100.0];
+        let inputs = [1.0, 2.0, 3.0, 4.0];
+
+        let (input, output) = layers(4, 1, 3, 1, Padding::Circular);
+        let shape = Shape::new(&input, &output);
+        let layer = Conv1d::try_from_weights(shape, Activation::Identity, &mut weights.into_iter())
+            .unwrap();
+        assert_eq!(propagate(&layer, &inputs, 4), [214.0, 321.0, 432.0, 143.0]);
+
+        let (input, output) = layers(4, 1, 3, 1, Padding::Zeros);
+        let shape = Shape::new(&input, &output);
+        let layer = Conv1d::try_from_weights(shape, Activation::Identity, &mut weights.into_iter())
+            .unwrap();
+        assert_eq!(propagate(&layer, &inputs, 4), [210.0, 321.0, 432.0, 43.0]);
+
+        let (input, output) = layers(4, 1, 3, 1, Padding::Valid);
+        let shape = Shape::new(&input, &output);
+        let layer = Conv1d::try_from_weights(shape, Activation::Identity, &mut weights.into_iter())
+            .unwrap();
+        assert_eq!(propagate(&layer, &inputs, 2), [321.0, 432.0]);
+    }
+
+    #[test]
+    fn circular_rotation_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (input, output) = layers(9, 3, 3, 1, Padding::Circular);
+        let layer: Conv1d<f32> = Conv1d::random(
+            Shape::new(&input, &output),
+            Activation::Tanh,
+            Initializer::default(),
+            &mut rng,
+        );
+        let inputs: Vec<f32> = (0..9).map(|i| i as f32 / 9.0).collect();
+        let mut rotated = inputs.clone();
+        rotated.rotate_right(1);
+
+        let outputs = propagate(&layer, &inputs, 27);
+        let rotated_outputs = propagate(&layer, &rotated, 27);
+
+        for (channel, rotated_channel) in outputs.chunks(9).zip(rotated_outputs.chunks(9)) {
+            let mut channel = channel.to_vec();
+            channel.rotate_right(1);
+            assert_eq!(channel, rotated_channel);
+        }
+    }
+
+    #[test]
+    fn lifecycle_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let (input, hidden) = layers(8, 2, 3, 1, Padding::Zeros);
+        let mut output = LayerTopology {
+            kind: LayerKind::Conv1d {
+                channels: 3,
+                kernel: 2,
+                stride: 2,
+                padding: Padding::Valid,
+            },
+            ..Default::default()
+        };
+        output.neurons = Shape::neurons(&hidden, &output).unwrap();
+
+        let shape = Shape::new(&hidden, &output);
+        let layer: Conv1d<f32> =
+            Conv1d::random(shape, Activation::Tanh, Initializer::default(), &mut rng);
+        let second_layer =
+            Conv1d::try_from_weights(shape, Activation::Tanh, &mut layer.weights()).unwrap();
+
+        assert_eq!(Shape::new(&input, &hidden).in_channels, 1);
+        assert_eq!(shape.in_channels, 2);
+        assert_eq!(output.neurons, 12);
+        assert_eq!(
+            Some(layer.weights().count()),
+            Conv1d::<f32>::weight_count(shape)
+        );
+        assert_eq!(layer.weights().count(), 3 * (2 * 2 + 1));
+        assert_eq!(layer, second_layer);
+    }
+}
@@ -0,0 +1,192 @@
+use std::{error::Error, fmt::Display};
+
+use crate::{Initializer, LayerKind};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetworkError {
+    /// The weight iterator ran out before every layer was filled.
+    TooFewWeights {
+        expected: usize,
+        received: usize,
+    },
+    /// The weight iterator still had items after every layer was filled.
+    /// Reading stops at the first extra weight, so `received` is
+    /// `expected + 1`.
+    TooManyWeights {
+        expected: usize,
+        received: usize,
+    },
+    /// A network needs at least an input and an output layer.
+    EmptyTopology,
+    /// A layer or convolution parameter is wider than [`crate::MAX_NEURONS`],
+    /// or counting weights up to this layer overflows or passes
+    /// [`crate::MAX_WEIGHTS`].
+    TopologyTooLarge {
+        layer: usize,
+    },
+    ZeroWidthLayer {
+        layer: usize,
+    },
+    NonFiniteWeight {
+        index: usize,
+        value: f64,
+    },
+    /// A neuron output `NaN` or an infinity; `layer` `0` is the network input.
+    NonFiniteActivation {
+        layer: usize,
+        neuron: usize,
+        value: f64,
+    },
+    /// The initializer of a layer cannot be sampled, see
+    /// [`crate::Initializer::is_valid`].
+    InvalidInitializer {
+        layer: usize,
+        initializer: Initializer,
+    },
+    /// A connection mask must hold one entry per weight of its layer.
+    MaskSize {
+        layer: usize,
+        expected: usize,
+        received: usize,
+    },
+    /// A convolution has a zero parameter, does not fit its input, or its
+    /// input does not split evenly into channels.
+    InvalidConvolution {
+        layer: usize,
+    },
+    /// A convolution layer must have one neuron per output channel and position.
+    ConvolutionSize {
+        layer: usize,
+        expected: usize,
+        received: usize,
+    },
+    /// A skip connection must come from a layer before the previous one, and
+    /// a convolution cannot take a [`crate::Merge::Concat`] one.
+    InvalidSkip {
+        layer: usize,
+        from: usize,
+    },
+    /// A [`crate::Merge::Add`] skip needs the skipped layer to be as wide as
+    /// the previous one.
+    SkipSize {
+        layer: usize,
+        expected: usize,
+        received: usize,
+    },
+    /// The operation does not handle skip connections.
+    UnsupportedSkip {
+        layer: usize,
+    },
+    /// The operation is only implemented for some kinds of layer.
+    UnsupportedLayer {
+        layer: usize,
+        kind: LayerKind,
+    },
+    /// Quantizing needs at least one calibration input.
+    EmptyCalibration,
+    /// An input vector is not as wide as the input layer.
+    InputSize {
+        expected: usize,
+        received: usize,
+    },
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::TooFewWeights { expected, received } => write!(
+                f,
+                "NetworkError: expected {} weights but only received {}",
+                expected, received
+            ),
+            NetworkError::TooManyWeights { expected, received } => write!(
+                f,
+                "NetworkError: expected {} weights but received at least {}",
+                expected, received
+            ),
+            NetworkError::EmptyTopology => {
+                write!(f, "NetworkError: topology needs at least two layers")
+            }
+            NetworkError::TopologyTooLarge { layer } => {
+                write!(f, "NetworkError: layer {} is too large to build", layer)
+            }
+            NetworkError::ZeroWidthLayer { layer } => {
+                write!(f, "NetworkError: layer {} has no neurons", layer)
+            }
+            NetworkError::NonFiniteWeight { index, value } => {
+                write!(f, "NetworkError: weight {} is not finite ({})", index, value)
+            }
+            NetworkError::NonFiniteActivation {
+                layer,
+                neuron,
+                value,
+            } => write!(
+                f,
+                "NetworkError: neuron {} of layer {} output {}",
+                neuron, layer, value
+            ),
+            NetworkError::InvalidInitializer { layer, initializer } => write!(
+                f,
+                "NetworkError: initializer of layer {} cannot be sampled ({:?})",
+                layer, initializer
+            ),
+            NetworkError::MaskSize {
+                layer,
+                expected,
+                received,
+            } => write!(
+                f,
+                "NetworkError: mask of layer {} has {} entries, expected {}",
+                layer, received, expected
+            ),
+            NetworkError::InvalidConvolution { layer } => write!(
+                f,
+                "NetworkError: convolution of layer {} does not fit its input",
+                layer
+            ),
+            NetworkError::ConvolutionSize {
+                layer,
+                expected,
+                received,
+            } => write!(
+                f,
+                "NetworkError: convolution of layer {} has {} neurons, expected {}",
+                layer, received, expected
+            ),
+            NetworkError::InvalidSkip { layer, from } => write!(
+                f,
+                "NetworkError: layer {} cannot skip from layer {}",
+                layer, from
+            ),
+            NetworkError::SkipSize {
+                layer,
+                expected,
+                received,
+            } => write!(
+                f,
+                "NetworkError: layer {} adds a skipped layer of {} neurons to {}",
+                layer, received, expected
+            ),
+            NetworkError::UnsupportedSkip { layer } => write!(
+                f,
+                "NetworkError: layer {} has a skip connection, which this operation does not support",
+                layer
+            ),
+            NetworkError::UnsupportedLayer { layer, kind } => write!(
+                f,
+                "NetworkError: layer {} is {:?}, which does not support this operation",
+                layer, kind
+            ),
+            NetworkError::EmptyCalibration => {
+                write!(f, "NetworkError: calibration needs at least one input")
+            }
+            NetworkError::InputSize { expected, received } => write!(
+                f,
+                "NetworkError: input has {} values, expected {}",
+                received, expected
+            ),
+        }
+    }
+}
+
+impl Error for NetworkError {}
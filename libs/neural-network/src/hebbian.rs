@@ -0,0 +1,250 @@
+use log::debug;
+use nalgebra::{DMatrix, DVectorSlice, DVectorSliceMut};
+use rand::{Rng, RngCore};
+
+use crate::{layer::Layer, Activation, Float, Initializer, NetworkError};
+
+/// Plastic weights are held within `[-LIMIT, LIMIT]` so a long lifetime of
+/// updates cannot run away.
+const LIMIT: f32 = 4.0;
+
+/// `learning_rate, a, b, c, d`
+const COEFFICIENTS: usize = 5;
+
+/// Coefficients of the ABCD rule
+/// `Δw = learning_rate * (a * x * y + b * x + c * y + d)`, where `x` is the
+/// input and `y` the output a weight connects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Rule<T: Float> {
+    learning_rate: T,
+    a: T,
+    b: T,
+    c: T,
+    d: T,
+}
+
+impl<T: Float> Rule<T> {
+    fn random(rng: &mut dyn RngCore) -> Self {
+        let learning_rate = T::cast_f32(rng.gen_range(0.0..=0.1));
+        let mut coefficient = || T::cast_f32(rng.gen_range(-1.0..=1.0));
+        Self {
+            learning_rate,
+            a: coefficient(),
+            b: coefficient(),
+            c: coefficient(),
+            d: coefficient(),
+        }
+    }
+
+    fn coefficients(&self) -> [T; COEFFICIENTS] {
+        [self.learning_rate, self.a, self.b, self.c, self.d]
+    }
+
+    fn cast<U: Float>(&self) -> Rule<U> {
+        let [learning_rate, a, b, c, d] = self
+            .coefficients()
+            .map(|coefficient| U::from_subset(&coefficient.as_f64()));
+        Rule {
+            learning_rate,
+            a,
+            b,
+            c,
+            d,
+        }
+    }
+}
+
+/// Feed-forward layer whose weights adapt after every step following an
+/// evolved Hebbian [`Rule`].
+///
+/// Weights are laid out like [`Layer`], followed by the rule's
+/// `learning_rate, a, b, c, d`. Those are the evolved starting weights; the
+/// plastic weights they grow into are never exported and
+/// [`Hebbian::reset_state`] puts them back to the start.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Hebbian<T: Float> {
+    evolved: Layer<T>,
+    rule: Rule<T>,
+    plastic: DMatrix<T>,
+}
+
+impl<T: Float> Hebbian<T> {
+    pub(crate) fn random(
+        input_neurons: usize,
+        output_neurons: usize,
+        activation: Activation,
+        initializer: Initializer,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        debug!(
+            "create new random hebbian layer with {} inputs and {} neurons",
+            input_neurons, output_neurons
+        );
+        let evolved = Layer::random(
+            input_neurons,
+            output_neurons,
+            activation,
+            initializer,
+            None,
+            rng,
+        );
+        Self {
+            plastic: evolved.weight_matrix().clone(),
+            rule: Rule::random(rng),
+            evolved,
+        }
+    }
+
+    pub(crate) fn try_from_weights(
+        input_neurons: usize,
+        output_neurons: usize,
+        activation: Activation,
+        weights: &mut dyn Iterator<Item = T>,
+    ) -> Result<Self, NetworkError> {
+        let evolved =
+            Layer::try_from_weights(input_neurons, output_neurons, activation, None, weights)?;
+        let expected = Self::weight_count(input_neurons, output_neurons).unwrap_or(usize::MAX);
+        let mut coefficients = [T::zero(); COEFFICIENTS];
+        for (received, coefficient) in coefficients.iter_mut().enumerate() {
+            *coefficient = weights.next().ok_or(NetworkError::TooFewWeights {
+                expected,
+                received: expected - COEFFICIENTS + received,
+            })?;
+        }
+        let [learning_rate, a, b, c, d] = coefficients;
+
+        Ok(Self {
+            plastic: evolved.weight_matrix().clone(),
+            rule: Rule {
+                learning_rate,
+                a,
+                b,
+                c,
+                d,
+            },
+            evolved,
+        })
+    }
+
+    /// `None` if the count overflows.
+    pub(crate) fn weight_count(input_neurons: usize, output_neurons: usize) -> Option<usize> {
+        Layer::<T>::weight_count(input_neurons, output_neurons, None)?.checked_add(COEFFICIENTS)
+    }
+
+    /// The layer as it starts every lifetime.
+    pub(crate) fn evolved(&self) -> &Layer<T> {
+        &self.evolved
+    }
+
+    pub(crate) fn weights(&self) -> impl Iterator<Item = T> + '_ {
+        self.evolved.weights().chain(self.rule.coefficients())
+    }
+
+    /// The same layer, plastic weights included, in another precision.
+    pub(crate) fn cast<U: Float>(&self) -> Hebbian<U> {
+        Hebbian {
+            evolved: self.evolved.cast(),
+            rule: self.rule.cast(),
+            plastic: self.plastic.map(|x| U::from_subset(&x.as_f64())),
+        }
+    }
+
+    pub(crate) fn reset_state(&mut self) {
+        self.plastic.copy_from(self.evolved.weight_matrix());
+    }
+
+    /// Computes the outputs with the current plastic weights without
+    /// adapting them.
+    pub(crate) fn step_into(&self, inputs: &DVectorSlice<T>, outputs: &mut DVectorSliceMut<T>) {
+        outputs.gemv(T::one(), &self.plastic, inputs, T::zero());
+        *outputs += self.evolved.biases();
+        let activation = self.evolved.activation();
+        outputs.apply(|x| *x = activation.apply(*x));
+    }
+
+    pub(crate) fn propagate_into(
+        &mut self,
+        inputs: &DVectorSlice<T>,
+        outputs: &mut DVectorSliceMut<T>,
+    ) {
+        self.step_into(inputs, outputs);
+
+        let Rule {
+            learning_rate,
+            a,
+            b,
+            c,
+            d,
+        } = self.rule;
+        let limit = T::cast_f32(LIMIT);
+        for (row, &y) in outputs.iter().enumerate() {
+            for (col, &x) in inputs.iter().enumerate() {
+                let weight = &mut self.plastic[(row, col)];
+                let delta = learning_rate * (a * x * y + b * x + c * y + d);
+                *weight = (*weight + delta).max(-limit).min(limit);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hebbian;
+    use crate::{Activation, Initializer};
+    use nalgebra::{dvector, DVector};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn step(layer: &mut Hebbian<f32>, input: f32) -> f32 {
+        let mut output = DVector::zeros(1);
+        layer.propagate_into(&dvector![input].rows(0, 1), &mut output.rows_mut(0, 1));
+        output[0]
+    }
+
+    #[test_log::test]
+    fn hebbian_learns_test() {
+        // y = w x, w starts at 0.5 and grows by 0.1 * (x * y + 0.5 x)
+        let mut weights = vec![0.0, 0.5, 0.1, 1.0, 0.5, 0.0, 0.0].into_iter();
+        let mut layer =
+            Hebbian::try_from_weights(1, 1, Activation::Identity, &mut weights).unwrap();
+
+        assert_eq!(step(&mut layer, 1.0), 0.5);
+        assert!((step(&mut layer, 1.0) - 0.6).abs() < 1e-6);
+        assert!((step(&mut layer, 1.0) - 0.71).abs() < 1e-6);
+
+        layer.reset_state();
+        assert_eq!(step(&mut layer, 1.0), 0.5);
+    }
+
+    #[test_log::test]
+    fn hebbian_weights_stay_bounded_test() {
+        let mut weights = vec![0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0].into_iter();
+        let mut layer =
+            Hebbian::try_from_weights(1, 1, Activation::Identity, &mut weights).unwrap();
+
+        for _ in 0..100 {
+            step(&mut layer, 1.0);
+        }
+        assert_eq!(step(&mut layer, 1.0), 4.0);
+    }
+
+    #[test_log::test]
+    fn hebbian_lifecycle_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut layer: Hebbian<f32> =
+            Hebbian::random(4, 3, Activation::Tanh, Initializer::default(), &mut rng);
+        let evolved: Vec<_> = layer.weights().collect();
+        let second_layer =
+            Hebbian::try_from_weights(4, 3, Activation::Tanh, &mut evolved.clone().into_iter())
+                .unwrap();
+
+        assert_eq!(Some(evolved.len()), Hebbian::<f32>::weight_count(4, 3));
+        assert_eq!(layer, second_layer);
+
+        let mut output = DVector::zeros(3);
+        let input = dvector![1.0, -1.0, 0.5, 0.25];
+        layer.propagate_into(&input.rows(0, 4), &mut output.rows_mut(0, 3));
+        assert_ne!(layer, second_layer);
+        assert_eq!(layer.weights().collect::<Vec<_>>(), evolved);
+    }
+}
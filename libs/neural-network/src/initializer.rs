@@ -0,0 +1,132 @@
+use rand::{Rng, RngCore};
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+
+/// How [`crate::Network::random`] draws the starting weights and biases of a
+/// layer. `fan_in` and `fan_out` are the number of values feeding into and out
+/// of each neuron.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Initializer {
+    /// Weights from `U(low, high)`, biases from `U(low, high]`.
+    Uniform { low: f32, high: f32 },
+    /// Weights and biases from `N(mean, std_dev²)`.
+    Normal { mean: f32, std_dev: f32 },
+    /// Glorot uniform: weights from `U(-a, a)` with `a = sqrt(6 / (fan_in + fan_out))`,
+    /// zero biases. Suited to tanh and sigmoid layers.
+    Xavier,
+    /// Kaiming normal: weights from `N(0, 2 / fan_in)`, zero biases. Suited to
+    /// ReLU layers.
+    He,
+    /// Every weight and bias is zero.
+    Zeros,
+}
+
+impl Default for Initializer {
+    fn default() -> Self {
+        Self::Uniform {
+            low: -1.0,
+            high: 1.0,
+        }
+    }
+}
+
+impl Initializer {
+    /// Whether the parameters describe a distribution that can be sampled:
+    /// finite, with `low < high` and a non-negative `std_dev`.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Initializer::Uniform { low, high } => low.is_finite() && high.is_finite() && low < high,
+            Initializer::Normal { mean, std_dev } => {
+                mean.is_finite() && std_dev.is_finite() && std_dev >= 0.0
+            }
+            Initializer::Xavier | Initializer::He | Initializer::Zeros => true,
+        }
+    }
+
+    pub fn weight(&self, fan_in: usize, fan_out: usize, rng: &mut dyn RngCore) -> f32 {
+        match *self {
+            Initializer::Uniform { low, high } => rng.gen_range(low..high),
+            Initializer::Normal { mean, std_dev } => sample_normal(mean, std_dev, rng),
+            Initializer::Xavier => {
+                let limit = (6.0 / (fan_in + fan_out) as f32).sqrt();
+                rng.gen_range(-limit..limit)
+            }
+            Initializer::He => sample_normal(0.0, (2.0 / fan_in as f32).sqrt(), rng),
+            Initializer::Zeros => 0.0,
+        }
+    }
+
+    pub fn bias(&self, rng: &mut dyn RngCore) -> f32 {
+        match *self {
+            Initializer::Uniform { low, high } => rng.gen_range(low..=high),
+            Initializer::Normal { mean, std_dev } => sample_normal(mean, std_dev, rng),
+            Initializer::Xavier | Initializer::He | Initializer::Zeros => 0.0,
+        }
+    }
+}
+
+fn sample_normal(mean: f32, std_dev: f32, rng: &mut dyn RngCore) -> f32 {
+    Normal::new(mean, std_dev)
+        .expect("standard deviation must be finite and non-negative")
+        .sample(rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Initializer;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn weights(initializer: Initializer, fan_in: usize, fan_out: usize) -> Vec<f32> {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        (0..10_000)
+            .map(|_| initializer.weight(fan_in, fan_out, &mut rng))
+            .collect()
+    }
+
+    fn variance(values: &[f32]) -> f32 {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32
+    }
+
+    #[test]
+    fn test_uniform() {
+        let values = weights(
+            Initializer::Uniform {
+                low: 2.0,
+                high: 3.0,
+            },
+            4,
+            4,
+        );
+        assert!(values.iter().all(|v| (2.0..3.0).contains(v)));
+    }
+
+    #[test]
+    fn test_xavier_bounds() {
+        let limit = (6.0f32 / 30.0).sqrt();
+        let values = weights(Initializer::Xavier, 12, 18);
+        assert!(values.iter().all(|v| v.abs() < limit));
+        assert!((variance(&values) - 2.0 / 30.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_he_variance() {
+        let values = weights(Initializer::He, 12, 18);
+        assert!((variance(&values) - 2.0 / 12.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_normal_and_zeros() {
+        let values = weights(
+            Initializer::Normal {
+                mean: 1.0,
+                std_dev: 0.5,
+            },
+            3,
+            3,
+        );
+        assert!((variance(&values) - 0.25).abs() < 0.02);
+        assert!(weights(Initializer::Zeros, 3, 3).iter().all(|&v| v == 0.0));
+    }
+}
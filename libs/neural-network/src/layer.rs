@@ -1,19 +1,30 @@
 use log::debug;
-use nalgebra::{Const, Dynamic, OMatrix, OVector, VecStorage};
-use rand::{Rng, RngCore};
+use nalgebra::{
+    Const, DMatrix, DVector, DVectorSlice, DVectorSliceMut, Dynamic, OMatrix, OVector, VecStorage,
+};
+use rand::RngCore;
+
+use crate::{Activation, Float, Initializer, NetworkError};
 
 const ONE: Const<1> = Const::<1>;
 
 #[derive(Debug, PartialEq)]
-pub(crate) struct Layer {
-    weights: nalgebra::OMatrix<f32, Dynamic, Dynamic>,
-    biases: nalgebra::OVector<f32, Dynamic>,
+pub(crate) struct Layer<T: Float> {
+    weights: nalgebra::OMatrix<T, Dynamic, Dynamic>,
+    biases: nalgebra::OVector<T, Dynamic>,
+    activation: Activation,
+    /// `false` marks a pruned connection. Its weight is held at zero and left
+    /// out of [`Layer::weights`]. `None` means fully connected.
+    mask: Option<DMatrix<bool>>,
 }
 
-impl Layer {
+impl<T: Float> Layer<T> {
     pub(crate) fn random(
         input_neurons: usize,
         output_neurons: usize,
+        activation: Activation,
+        initializer: Initializer,
+        mask: Option<&[bool]>,
         rng: &mut dyn RngCore,
     ) -> Self {
         debug!(
@@ -23,38 +34,165 @@ impl Layer {
         let ncols = Dynamic::new(input_neurons);
         let nrows = Dynamic::new(output_neurons);
 
-        let weights = OMatrix::from_fn_generic(nrows, ncols, |_, _| rng.gen_range(-1.0..1.0));
-        let biases = OVector::from_fn_generic(nrows, ONE, |_, _| rng.gen_range(-1.0..=1.0));
+        let mask = mask.map(|mask| DMatrix::from_row_slice(output_neurons, input_neurons, mask));
+        let mut weights = OMatrix::from_fn_generic(nrows, ncols, |_, _| {
+            T::cast_f32(initializer.weight(input_neurons, output_neurons, rng))
+        });
+        if let Some(mask) = &mask {
+            weights.zip_apply(mask, |weight, connected| {
+                if !connected {
+                    *weight = T::zero()
+                }
+            });
+        }
+        let biases =
+            OVector::from_fn_generic(nrows, ONE, |_, _| T::cast_f32(initializer.bias(rng)));
 
-        Self { weights, biases }
+        Self {
+            weights,
+            biases,
+            activation,
+            mask,
+        }
     }
 
     pub(crate) fn propagate(
         &self,
-        inputs: &nalgebra::Vector<f32, Dynamic, VecStorage<f32, nalgebra::Dynamic, Const<1>>>,
-    ) -> nalgebra::Vector<f32, Dynamic, VecStorage<f32, nalgebra::Dynamic, Const<1>>> {
-        (&self.weights * inputs + &self.biases).map(|x| x.max(0.0))
+        inputs: &nalgebra::Vector<T, Dynamic, VecStorage<T, nalgebra::Dynamic, Const<1>>>,
+    ) -> nalgebra::Vector<T, Dynamic, VecStorage<T, nalgebra::Dynamic, Const<1>>> {
+        (&self.weights * inputs + &self.biases).map(|x| self.activation.apply(x))
+    }
+
+    /// Writes the layer output into `outputs` without allocating.
+    pub(crate) fn propagate_into(
+        &self,
+        inputs: &DVectorSlice<T>,
+        outputs: &mut DVectorSliceMut<T>,
+    ) {
+        outputs.gemv(T::one(), &self.weights, inputs, T::zero());
+        *outputs += &self.biases;
+        outputs.apply(|x| *x = self.activation.apply(*x));
+    }
+
+    /// Propagates every column of `inputs` at once.
+    pub(crate) fn propagate_batch(&self, inputs: &DMatrix<T>) -> DMatrix<T> {
+        let mut outputs = self.preactivate_batch(inputs);
+        outputs.apply(|x| *x = self.activation.apply(*x));
+        outputs
+    }
+
+    /// `W * inputs + b` for every column of `inputs`, before the activation.
+    pub(crate) fn preactivate_batch(&self, inputs: &DMatrix<T>) -> DMatrix<T> {
+        let mut outputs = &self.weights * inputs;
+        for mut column in outputs.column_iter_mut() {
+            column += &self.biases;
+        }
+        outputs
+    }
+
+    pub(crate) fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    pub(crate) fn weight_matrix(&self) -> &DMatrix<T> {
+        &self.weights
+    }
+
+    pub(crate) fn biases(&self) -> &DVector<T> {
+        &self.biases
+    }
+
+    /// Adds `deltas`, laid out like [`Layer::weights`], to the parameters.
+    pub(crate) fn add_deltas(&mut self, deltas: &mut dyn Iterator<Item = T>) {
+        for row in 0..self.biases.len() {
+            self.biases[row] += deltas.next().expect("failed to receive bias delta");
+            for col in 0..self.weights.ncols() {
+                if self.is_connected(row, col) {
+                    self.weights[(row, col)] +=
+                        deltas.next().expect("failed to receive weight delta");
+                }
+            }
+        }
     }
 
-    pub(crate) fn weights(&self) -> impl Iterator<Item = f32> + '_ {
+    pub(crate) fn is_connected(&self, row: usize, col: usize) -> bool {
+        self.mask
+            .as_ref()
+            .map(|mask| mask[(row, col)])
+            .unwrap_or(true)
+    }
+
+    /// Row-major copy of the connection mask, as stored in
+    /// [`crate::LayerTopology::mask`].
+    pub(crate) fn mask(&self) -> Option<Vec<bool>> {
+        self.mask
+            .as_ref()
+            .map(|mask| mask.transpose().as_slice().to_vec())
+    }
+
+    /// Masks every remaining connection whose weight magnitude is below
+    /// `threshold` and returns how many were removed.
+    pub(crate) fn prune(&mut self, threshold: T) -> usize {
+        let (nrows, ncols) = self.weights.shape();
+        let mask = self
+            .mask
+            .get_or_insert_with(|| DMatrix::from_element(nrows, ncols, true));
+        let mut pruned = 0;
+        for (weight, connected) in self.weights.iter_mut().zip(mask.iter_mut()) {
+            if *connected && weight.abs() < threshold {
+                *weight = T::zero();
+                *connected = false;
+                pruned += 1;
+            }
+        }
+        pruned
+    }
+
+    pub(crate) fn weights(&self) -> impl Iterator<Item = T> + '_ {
         use std::iter::once;
 
         self.weights
             .row_iter()
-            .zip(self.biases.into_iter())
-            .flat_map(|(row, bias)| {
+            .zip(self.biases.iter())
+            .enumerate()
+            .flat_map(move |(row, (weights, bias))| {
+                let connected = weights
+                    .iter()
+                    .enumerate()
+                    .filter(move |&(col, _)| self.is_connected(row, col))
+                    .map(|(_, weight)| weight);
                 once(bias)
-                    .chain(row.into_iter())
+                    .chain(connected)
                     .map(|x| x.to_owned())
                     .collect::<Vec<_>>()
             })
     }
 
+    #[cfg(test)]
     pub(crate) fn from_weights(
         input_neurons: usize,
         output_neurons: usize,
-        neuron_weights: &mut dyn Iterator<Item = f32>,
+        activation: Activation,
+        mask: Option<&[bool]>,
+        neuron_weights: &mut dyn Iterator<Item = T>,
     ) -> Self {
+        Self::try_from_weights(
+            input_neurons,
+            output_neurons,
+            activation,
+            mask,
+            neuron_weights,
+        )
+        .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub(crate) fn try_from_weights(
+        input_neurons: usize,
+        output_neurons: usize,
+        activation: Activation,
+        mask: Option<&[bool]>,
+        neuron_weights: &mut dyn Iterator<Item = T>,
+    ) -> Result<Self, NetworkError> {
         debug!(
             "create new layer from weights dim ({}, {}) and bias dim ({})",
             output_neurons, input_neurons, output_neurons
@@ -62,27 +200,69 @@ impl Layer {
         let ncols = Dynamic::new(input_neurons);
         let nrows = Dynamic::new(output_neurons);
 
-        let mut weights = OMatrix::from_element_generic(nrows, ncols, 0.0);
-        let mut biases = OVector::from_element_generic(nrows, ONE, 0.0);
+        let mut weights = OMatrix::from_element_generic(nrows, ncols, T::zero());
+        let mut biases = OVector::from_element_generic(nrows, ONE, T::zero());
+
+        let expected =
+            Self::weight_count(input_neurons, output_neurons, mask).unwrap_or(usize::MAX);
+        let mask = mask.map(|mask| DMatrix::from_row_slice(output_neurons, input_neurons, mask));
+        let mut received = 0;
+        let mut next = || {
+            let weight = neuron_weights
+                .next()
+                .ok_or(NetworkError::TooFewWeights { expected, received });
+            received += 1;
+            weight
+        };
 
         for row in 0..output_neurons {
-            biases[(row)] = neuron_weights.next().expect("failed to receive bias");
+            biases[row] = next()?;
             for col in 0..input_neurons {
-                debug!("adding weight from iterator at pos ({}, {})", row, col);
-                weights[(row, col)] = neuron_weights
-                    .next()
-                    .expect(&format!("failed to receive weight ({}, {})", row, col));
+                if mask.as_ref().map(|mask| mask[(row, col)]).unwrap_or(true) {
+                    debug!("adding weight from iterator at pos ({}, {})", row, col);
+                    weights[(row, col)] = next()?;
+                }
             }
         }
 
-        Self { weights, biases }
+        Ok(Self {
+            weights,
+            biases,
+            activation,
+            mask,
+        })
+    }
+
+    /// The same layer in another precision.
+    pub(crate) fn cast<U: Float>(&self) -> Layer<U> {
+        Layer {
+            weights: self.weights.map(|x| U::from_subset(&x.as_f64())),
+            biases: self.biases.map(|x| U::from_subset(&x.as_f64())),
+            activation: self.activation,
+            mask: self.mask.clone(),
+        }
+    }
+
+    /// `None` if the count overflows.
+    pub(crate) fn weight_count(
+        input_neurons: usize,
+        output_neurons: usize,
+        mask: Option<&[bool]>,
+    ) -> Option<usize> {
+        match mask {
+            Some(mask) => {
+                output_neurons.checked_add(mask.iter().filter(|&&connected| connected).count())
+            }
+            None => output_neurons.checked_mul(input_neurons.checked_add(1)?),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Layer;
-    use nalgebra::{dvector, matrix, vector};
+    use crate::{Activation, Initializer, NetworkError};
+    use nalgebra::{dmatrix, dvector, matrix, vector};
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
 
@@ -91,8 +271,21 @@ mod tests {
         let (input_neurons, output_neurons) = (10, 10);
         let mut rng = ChaCha8Rng::from_seed(Default::default());
 
-        let layer = Layer::random(input_neurons, output_neurons, &mut rng);
-        let second_layer = Layer::from_weights(input_neurons, output_neurons, &mut layer.weights());
+        let layer: Layer<f32> = Layer::random(
+            input_neurons,
+            output_neurons,
+            Activation::Tanh,
+            Initializer::default(),
+            None,
+            &mut rng,
+        );
+        let second_layer = Layer::from_weights(
+            input_neurons,
+            output_neurons,
+            Activation::Tanh,
+            None,
+            &mut layer.weights(),
+        );
 
         assert_eq!(layer.weights, second_layer.weights);
         assert_eq!(layer.biases, second_layer.biases);
@@ -106,7 +299,13 @@ mod tests {
         let (output_neurons, input_neurons) = (actual_weights.nrows(), actual_weights.ncols());
 
         let mut neuron_weights = vec![1.0, 2.0, 3.0, 1.0, 4.0, 5.0, 1.0, 6.0, 7.0].into_iter();
-        let layer = Layer::from_weights(input_neurons, output_neurons, &mut neuron_weights);
+        let layer = Layer::from_weights(
+            input_neurons,
+            output_neurons,
+            Activation::ReLU,
+            None,
+            &mut neuron_weights,
+        );
 
         assert_eq!(actual_weights, layer.weights);
         assert_eq!(actual_biases, layer.biases);
@@ -117,4 +316,96 @@ mod tests {
             layer.propagate(&input_vector)
         );
     }
+
+    #[test_log::test]
+    fn propagate_activation_test() {
+        let mut neuron_weights = vec![0.0, -1.0, 0.0, 1.0].into_iter();
+        let layer = Layer::from_weights(1, 2, Activation::Tanh, None, &mut neuron_weights);
+
+        let output = layer.propagate(&dvector![2.0]);
+        assert_eq!(output, dvector![(-2.0f32).tanh(), 2.0f32.tanh()]);
+    }
+
+    #[test_log::test]
+    fn propagate_batch_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let layer = Layer::random(
+            3,
+            2,
+            Activation::Tanh,
+            Initializer::default(),
+            None,
+            &mut rng,
+        );
+        let inputs = dmatrix![1.0, 0.0, -1.0, 0.5; 0.0, 2.0, 0.0, 0.5; 3.0, 0.0, 1.0, 0.5];
+
+        let outputs = layer.propagate_batch(&inputs);
+
+        for (input, output) in inputs.column_iter().zip(outputs.column_iter()) {
+            assert_eq!(layer.propagate(&input.clone_owned()), output);
+        }
+    }
+
+    #[test_log::test]
+    fn propagate_into_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let layer = Layer::random(
+            3,
+            2,
+            Activation::Sigmoid,
+            Initializer::default(),
+            None,
+            &mut rng,
+        );
+        let inputs = dvector![1.0, -2.0, 0.5];
+        let mut outputs = dvector![0.0, 0.0, 7.0];
+
+        layer.propagate_into(&inputs.rows(0, 3), &mut outputs.rows_mut(0, 2));
+
+        assert_eq!(layer.propagate(&inputs), outputs.rows(0, 2));
+        assert_eq!(outputs[2], 7.0);
+    }
+
+    #[test_log::test]
+    fn try_from_weights_too_few_test() {
+        let mut neuron_weights = vec![1.0, 2.0, 3.0, 1.0].into_iter();
+        let error = Layer::try_from_weights(2, 2, Activation::ReLU, None, &mut neuron_weights);
+
+        assert_eq!(
+            error,
+            Err(NetworkError::TooFewWeights {
+                expected: 6,
+                received: 4
+            })
+        );
+    }
+
+    #[test_log::test]
+    fn masked_weights_test() {
+        let mask = [true, false, false, true];
+        let mut neuron_weights = vec![0.5, 2.0, -0.5, 3.0].into_iter();
+        let layer =
+            Layer::from_weights(2, 2, Activation::Identity, Some(&mask), &mut neuron_weights);
+
+        assert_eq!(layer.weights, dmatrix![2.0, 0.0; 0.0, 3.0]);
+        assert_eq!(
+            layer.weights().collect::<Vec<_>>(),
+            vec![0.5, 2.0, -0.5, 3.0]
+        );
+        assert_eq!(Layer::<f32>::weight_count(2, 2, Some(&mask)), Some(4));
+        assert_eq!(layer.propagate(&dvector![1.0, 1.0]), dvector![2.5, 2.5]);
+        assert_eq!(layer.mask(), Some(mask.to_vec()));
+    }
+
+    #[test_log::test]
+    fn prune_test() {
+        let mut neuron_weights = vec![1.0, 0.1, -2.0, 1.0, 0.3, -0.05].into_iter();
+        let mut layer = Layer::from_weights(2, 2, Activation::Identity, None, &mut neuron_weights);
+
+        assert_eq!(layer.prune(0.5), 3);
+        assert_eq!(layer.mask(), Some(vec![false, true, false, false]));
+        assert_eq!(layer.weights().collect::<Vec<_>>(), vec![1.0, -2.0, 1.0]);
+        assert_eq!(layer.propagate(&dvector![1.0, 1.0]), dvector![-1.0, 1.0]);
+        assert_eq!(layer.prune(0.5), 0);
+    }
 }
@@ -1,88 +1,727 @@
 extern crate nalgebra;
 
+use nalgebra::{DMatrix, DVector, RealField};
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+pub mod activation;
+pub use activation::Activation;
+
+pub mod codegen;
+
+mod conv;
+pub use conv::Padding;
+
+pub mod error;
+pub use error::NetworkError;
+
+mod hebbian;
+
+pub mod initializer;
+pub use initializer::Initializer;
 
 pub mod layer;
 use layer::Layer;
 
+pub mod neat;
+
+pub mod onnx;
+
+mod network_layer;
+use network_layer::NetworkLayer;
+
+pub mod quantize;
+pub use quantize::QuantizedNetwork;
+
+mod recurrent;
+
+pub mod render;
+
+pub mod sanitize;
+pub use sanitize::NonFinitePolicy;
+
+pub mod skip;
+pub use skip::{Merge, Skip};
+
+pub mod serialization;
+pub use serialization::{Format, SerializationError};
+
+pub mod train;
+pub use train::{Loss, Optimizer, Trainer};
+
+/// Widest layer, and largest convolution parameter, a [`Network`] accepts.
+/// Topologies are checked against it before anything is allocated, so a
+/// corrupt file cannot ask for an absurd allocation.
+pub const MAX_NEURONS: usize = 1 << 16;
+
+/// Most weights a [`Network`] accepts, for the same reason as
+/// [`MAX_NEURONS`].
+pub const MAX_WEIGHTS: usize = 1 << 26;
+
+/// Scalar type a [`Network`] computes in, in practice `f32` or `f64`.
+pub trait Float: RealField + Copy {
+    /// Exact for both `f32` and `f64`.
+    fn cast_f32(x: f32) -> Self {
+        Self::from_subset(&f64::from(x))
+    }
+
+    /// Exact for both `f32` and `f64`.
+    fn as_f64(self) -> f64 {
+        self.to_subset_unchecked()
+    }
+}
+
+impl<T: RealField + Copy> Float for T {}
+
+/// Scratch buffers reused across [`Network::propagate_into`] calls. The
+/// buffers grow to the widest layer input on first use, plus a copy of every
+/// layer output a [`Skip`] reads, and are never shrunk.
 #[derive(Debug)]
-pub struct Network {
-    layers: Vec<Layer>,
+pub struct Workspace<T: Float = f32> {
+    front: DVector<T>,
+    back: DVector<T>,
+    /// Indexed like the topology, only skipped layers are sized.
+    skipped: Vec<DVector<T>>,
+}
+
+impl<T: Float> Default for Workspace<T> {
+    fn default() -> Self {
+        Self {
+            front: DVector::zeros(0),
+            back: DVector::zeros(0),
+            skipped: Vec::new(),
+        }
+    }
+}
+
+impl<T: Float> Workspace<T> {
+    fn reserve(&mut self, neurons: usize) {
+        if self.front.len() < neurons {
+            self.front.resize_vertically_mut(neurons, T::zero());
+            self.back.resize_vertically_mut(neurons, T::zero());
+        }
+    }
+
+    fn reserve_skips(&mut self, topology: &[LayerTopology]) {
+        if self.skipped.len() < topology.len() {
+            self.skipped.resize(topology.len(), DVector::zeros(0));
+        }
+        for skip in topology.iter().filter_map(|layer| layer.skip) {
+            let neurons = topology[skip.from].neurons;
+            if self.skipped[skip.from].len() < neurons {
+                self.skipped[skip.from].resize_vertically_mut(neurons, T::zero());
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
+pub struct Network<T: Float = f32> {
+    topology: Vec<LayerTopology>,
+    layers: Vec<NetworkLayer<T>>,
+}
+
+/// Shape of a single layer. The activation and initializer of the first
+/// (input) layer are ignored, every other layer applies its activation to its
+/// outputs. The input layer's kind only matters to a following
+/// [`LayerKind::Conv1d`], which reads its channel count.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct LayerTopology {
     pub neurons: usize,
+    pub activation: Activation,
+    #[serde(default)]
+    pub kind: LayerKind,
+    /// Only used by [`Network::random`].
+    #[serde(default)]
+    pub initializer: Initializer,
+    /// Connections from the layer input, row-major with one row per neuron of
+    /// this layer: `mask[i * inputs + j]` is `false` if input `j` is cut from
+    /// neuron `i`. `inputs` is the previous layer's width, plus the skipped
+    /// layer's for a [`Merge::Concat`] skip. Cut connections have no weight, so they are
+    /// skipped by [`Network::weights`] and [`Network::from_weights`]. `None`
+    /// connects everything; only feed-forward layers can be masked.
+    #[serde(default)]
+    pub mask: Option<Vec<bool>>,
+    /// Extra input from an earlier layer, see [`Skip`]. Ignored on the input
+    /// layer.
+    #[serde(default)]
+    pub skip: Option<Skip>,
 }
 
-impl Network {
-    pub fn random(mut rng: &mut dyn RngCore, layers: &[LayerTopology]) -> Self {
-        assert!(layers.len() > 1);
-        let layers = layers
-            .windows(2)
-            .map(|layers| Layer::random(layers[0].neurons, layers[1].neurons, &mut rng))
+/// How a layer turns its inputs into outputs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LayerKind {
+    /// Stateless `f(W x + b)`.
+    #[default]
+    FeedForward,
+    /// Elman recurrent layer, `h' = f(W x + U h + b)`. The hidden state is
+    /// carried between calls to [`Network::propagate`].
+    Elman,
+    /// Gated recurrent unit using the layer activation for its candidate state.
+    Gru,
+    /// Feed-forward layer whose weights keep learning during
+    /// [`Network::propagate`] by an evolved Hebbian ABCD rule,
+    /// `Δw = η (A x y + B x + C y + D)`. [`Network::weights`] holds the starting
+    /// weights followed by `η, A, B, C, D`, never the learned weights.
+    Hebbian,
+    /// 1D convolution with `channels` kernels of `kernel` taps slid along the
+    /// previous layer in steps of `stride`. The previous layer is read as the
+    /// channels of a previous convolution, or as a single channel otherwise.
+    /// `neurons` must be `channels` times the output length, see [`Padding`].
+    Conv1d {
+        channels: usize,
+        kernel: usize,
+        stride: usize,
+        padding: Padding,
+    },
+}
+
+impl<T: Float> Network<T> {
+    pub fn random(rng: &mut dyn RngCore, topology: &[LayerTopology]) -> Self {
+        Self::try_random(rng, topology).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_random(
+        mut rng: &mut dyn RngCore,
+        topology: &[LayerTopology],
+    ) -> Result<Self, NetworkError> {
+        Self::check_topology(topology)?;
+
+        let layers = (1..topology.len())
+            .map(|layer| {
+                let input = skip::input_topology(topology, layer);
+                NetworkLayer::random(&input, &topology[layer], &mut rng)
+            })
             .collect();
 
-        Self { layers }
+        Ok(Self {
+            topology: topology.to_vec(),
+            layers,
+        })
     }
 
-    pub fn from_weights(layers: &[LayerTopology], weights: impl IntoIterator<Item = f32>) -> Self {
-        assert!(layers.len() > 1);
+    pub fn from_weights(topology: &[LayerTopology], weights: impl IntoIterator<Item = T>) -> Self {
+        Self::try_from_weights(topology, weights).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_from_weights(
+        topology: &[LayerTopology],
+        weights: impl IntoIterator<Item = T>,
+    ) -> Result<Self, NetworkError> {
+        Self::check_topology(topology)?;
+
+        let expected = Network::weight_count(topology);
+        // one extra weight is enough to tell there are too many, and keeps an
+        // endless iterator from being drained
+        let weights: Vec<T> = weights.into_iter().take(expected + 1).collect();
+
+        if weights.len() < expected {
+            return Err(NetworkError::TooFewWeights {
+                expected,
+                received: weights.len(),
+            });
+        }
+        if weights.len() > expected {
+            return Err(NetworkError::TooManyWeights {
+                expected,
+                received: weights.len(),
+            });
+        }
+        if let Some((index, &value)) = weights.iter().enumerate().find(|(_, w)| !w.is_finite()) {
+            return Err(NetworkError::NonFiniteWeight {
+                index,
+                value: value.as_f64(),
+            });
+        }
 
         let mut weights = weights.into_iter();
+        let layers = (1..topology.len())
+            .map(|layer| {
+                let input = skip::input_topology(topology, layer);
+                NetworkLayer::try_from_weights(&input, &topology[layer], &mut weights)
+            })
+            .collect::<Result<_, _>>()?;
 
-        let layers = layers
-            .windows(2)
-            .map(|layers| Layer::from_weights(layers[0].neurons, layers[1].neurons, &mut weights))
-            .collect();
+        Ok(Self {
+            topology: topology.to_vec(),
+            layers,
+        })
+    }
 
-        if weights.next().is_some() {
-            let count = weights.count();
-            panic!(
-                "Network tried to take too many weights. {} items remaining",
-                count
-            );
+    fn check_topology(topology: &[LayerTopology]) -> Result<(), NetworkError> {
+        if topology.len() < 2 {
+            return Err(NetworkError::EmptyTopology);
         }
+        if let Some(layer) = topology.iter().position(|layer| layer.neurons == 0) {
+            return Err(NetworkError::ZeroWidthLayer { layer });
+        }
+        let too_large = |layer: &LayerTopology| {
+            let widths = match layer.kind {
+                LayerKind::Conv1d {
+                    channels,
+                    kernel,
+                    stride,
+                    ..
+                } => [layer.neurons, channels, kernel, stride],
+                _ => [layer.neurons; 4],
+            };
+            widths.iter().any(|&width| width > MAX_NEURONS)
+        };
+        if let Some(layer) = topology.iter().position(too_large) {
+            return Err(NetworkError::TopologyTooLarge { layer });
+        }
+        // the input layer draws no weights, so its initializer is never used
+        if let Some(layer) =
+            (1..topology.len()).find(|&layer| !topology[layer].initializer.is_valid())
+        {
+            return Err(NetworkError::InvalidInitializer {
+                layer,
+                initializer: topology[layer].initializer,
+            });
+        }
+        for layer in 1..topology.len() {
+            let output = &topology[layer];
+            if let Some(Skip { from, merge }) = output.skip {
+                let concat_into_conv =
+                    merge == Merge::Concat && matches!(output.kind, LayerKind::Conv1d { .. });
+                if from + 1 >= layer || concat_into_conv {
+                    return Err(NetworkError::InvalidSkip { layer, from });
+                }
+                let (expected, received) = (topology[layer - 1].neurons, topology[from].neurons);
+                if merge == Merge::Add && expected != received {
+                    return Err(NetworkError::SkipSize {
+                        layer,
+                        expected,
+                        received,
+                    });
+                }
+            }
 
-        Self { layers }
+            let input = skip::input_topology(topology, layer);
+            if let LayerKind::Conv1d { .. } = output.kind {
+                match conv::Shape::neurons(&input, output) {
+                    None => return Err(NetworkError::InvalidConvolution { layer }),
+                    Some(expected) if expected != output.neurons => {
+                        return Err(NetworkError::ConvolutionSize {
+                            layer,
+                            expected,
+                            received: output.neurons,
+                        })
+                    }
+                    Some(_) => {}
+                }
+            }
+            if let Some(mask) = &output.mask {
+                if output.kind != LayerKind::FeedForward {
+                    return Err(NetworkError::UnsupportedLayer {
+                        layer,
+                        kind: output.kind,
+                    });
+                }
+                let expected = input.neurons.checked_mul(output.neurons);
+                if expected != Some(mask.len()) {
+                    let expected = expected.unwrap_or(usize::MAX);
+                    return Err(NetworkError::MaskSize {
+                        layer,
+                        expected,
+                        received: mask.len(),
+                    });
+                }
+            }
+        }
+        Network::checked_weight_count(topology).map(|_| ())
     }
 
-    pub fn weights(&self) -> impl Iterator<Item = f32> + '_ {
-        self.layers.iter().flat_map(|layer| layer.weights())
+    /// The same network in another precision, hidden state included.
+    /// Widening `f32` to `f64` is exact, so `network.cast::<f64>().cast::<f32>()`
+    /// gives back an identical network; narrowing rounds every weight to the
+    /// nearest `f32`.
+    pub fn cast<U: Float>(&self) -> Network<U> {
+        Network {
+            topology: self.topology.clone(),
+            layers: self.layers.iter().map(NetworkLayer::cast).collect(),
+        }
     }
 
-    pub fn propagate(&self, inputs: Vec<f32>) -> Vec<f32> {
-        let vector = nalgebra::DVector::from_vec(inputs);
+    /// Every layer as a plain feed-forward layer, for operations that only
+    /// handle a simple chain of them.
+    pub(crate) fn feed_forward_chain(&self) -> Result<Vec<&Layer<T>>, NetworkError> {
         self.layers
             .iter()
-            .fold(vector, |inputs, layer| layer.propagate(&inputs))
+            .zip(&self.topology[1..])
+            .enumerate()
+            .map(|(index, (layer, shape))| match layer {
+                _ if shape.skip.is_some() => {
+                    Err(NetworkError::UnsupportedSkip { layer: index + 1 })
+                }
+                NetworkLayer::FeedForward(layer) => Ok(layer),
+                _ => Err(NetworkError::UnsupportedLayer {
+                    layer: index + 1,
+                    kind: shape.kind,
+                }),
+            })
+            .collect()
+    }
+
+    pub fn topology(&self) -> &[LayerTopology] {
+        &self.topology
+    }
+
+    pub fn weights(&self) -> impl Iterator<Item = T> + '_ {
+        self.layers.iter().flat_map(|layer| layer.weights())
+    }
+
+    /// Cuts every feed-forward connection whose weight magnitude is below
+    /// `threshold` and returns how many were cut. The masks are recorded in
+    /// [`Network::topology`], so `weights()` shrinks accordingly and
+    /// `Network::from_weights(network.topology(), network.weights())` still
+    /// round-trips. Biases and recurrent layers are never pruned.
+    pub fn prune(&mut self, threshold: T) -> usize {
+        let mut pruned = 0;
+        for (layer, topology) in self.layers.iter_mut().zip(&mut self.topology[1..]) {
+            if let NetworkLayer::FeedForward(layer) = layer {
+                pruned += layer.prune(threshold);
+                topology.mask = layer.mask();
+            }
+        }
+        pruned
+    }
+
+    /// Clears the hidden state of every recurrent layer and puts the weights of
+    /// every [`LayerKind::Hebbian`] layer back to where they started.
+    pub fn reset_state(&mut self) {
+        self.layers.iter_mut().for_each(NetworkLayer::reset_state);
+    }
+
+    pub fn propagate(&mut self, inputs: Vec<T>) -> Vec<T> {
+        // without a policy nothing is checked
+        self.forward(inputs, None)
+            .unwrap()
+            .pop()
+            .unwrap()
             .data
-            .as_vec()
-            .to_owned()
+            .into()
+    }
+
+    /// Same as [`Network::propagate`] but returns the activations of every
+    /// layer, starting with `inputs` and ending with the network output.
+    pub fn propagate_traced(&mut self, inputs: Vec<T>) -> Vec<Vec<T>> {
+        Self::collect_trace(self.forward(inputs, None).unwrap())
+    }
+
+    /// Same as [`Network::propagate`], but the inputs and every layer output
+    /// are checked for `NaN` and infinities, which `policy` either reports or
+    /// replaces before the next layer reads them.
+    pub fn try_propagate(
+        &mut self,
+        inputs: Vec<T>,
+        policy: NonFinitePolicy,
+    ) -> Result<Vec<T>, NetworkError> {
+        Ok(self
+            .forward(inputs, Some(policy))?
+            .pop()
+            .unwrap()
+            .data
+            .into())
+    }
+
+    /// Same as [`Network::propagate_traced`], checked like
+    /// [`Network::try_propagate`].
+    pub fn try_propagate_traced(
+        &mut self,
+        inputs: Vec<T>,
+        policy: NonFinitePolicy,
+    ) -> Result<Vec<Vec<T>>, NetworkError> {
+        Ok(Self::collect_trace(self.forward(inputs, Some(policy))?))
+    }
+
+    fn collect_trace(outputs: Vec<DVector<T>>) -> Vec<Vec<T>> {
+        outputs
+            .into_iter()
+            .map(|outputs| outputs.data.into())
+            .collect()
+    }
+
+    /// Output of every layer. All of them are kept since skip connections may
+    /// read any earlier one.
+    fn forward(
+        &mut self,
+        inputs: Vec<T>,
+        policy: Option<NonFinitePolicy>,
+    ) -> Result<Vec<DVector<T>>, NetworkError> {
+        let mut outputs = Vec::with_capacity(self.topology.len());
+        outputs.push(DVector::from_vec(inputs));
+        for (index, (layer, shape)) in self.layers.iter_mut().zip(&self.topology[1..]).enumerate() {
+            if let Some(policy) = policy {
+                policy.apply(index, outputs[index].as_mut_slice())?;
+            }
+            let next = layer.propagate(&skip::layer_input(&outputs, shape.skip), shape.neurons);
+            outputs.push(next);
+        }
+        if let (Some(policy), Some(output)) = (policy, outputs.last_mut()) {
+            policy.apply(self.layers.len(), output.as_mut_slice())?;
+        }
+        Ok(outputs)
+    }
+
+    /// A workspace already sized for this network.
+    pub fn workspace(&self) -> Workspace<T> {
+        let mut workspace = Workspace::default();
+        workspace.reserve(self.widest_layer());
+        workspace.reserve_skips(&self.topology);
+        workspace
+    }
+
+    /// Widest layer output or merged layer input.
+    fn widest_layer(&self) -> usize {
+        (1..self.topology.len())
+            .map(|layer| skip::input_topology(&self.topology, layer).neurons)
+            .chain(self.topology.iter().map(|layer| layer.neurons))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Allocation-free version of [`Network::propagate`]: `inputs` must be as
+    /// wide as the input layer and `outputs` as wide as the output layer.
+    pub fn propagate_into(
+        &mut self,
+        inputs: &[T],
+        outputs: &mut [T],
+        workspace: &mut Workspace<T>,
+    ) {
+        // without a policy nothing is checked
+        self.forward_into(inputs, outputs, workspace, None).unwrap()
+    }
+
+    /// Same as [`Network::propagate_into`], checked like
+    /// [`Network::try_propagate`].
+    pub fn try_propagate_into(
+        &mut self,
+        inputs: &[T],
+        outputs: &mut [T],
+        workspace: &mut Workspace<T>,
+        policy: NonFinitePolicy,
+    ) -> Result<(), NetworkError> {
+        self.forward_into(inputs, outputs, workspace, Some(policy))
+    }
+
+    fn forward_into(
+        &mut self,
+        inputs: &[T],
+        outputs: &mut [T],
+        workspace: &mut Workspace<T>,
+        policy: Option<NonFinitePolicy>,
+    ) -> Result<(), NetworkError> {
+        assert_eq!(inputs.len(), self.topology[0].neurons);
+        assert_eq!(
+            outputs.len(),
+            self.topology[self.topology.len() - 1].neurons
+        );
+
+        workspace.reserve(self.widest_layer());
+        workspace.reserve_skips(&self.topology);
+        workspace
+            .front
+            .rows_mut(0, inputs.len())
+            .copy_from_slice(inputs);
+
+        let topology = &self.topology;
+        for (index, layer) in self.layers.iter_mut().enumerate() {
+            let Workspace {
+                front,
+                back,
+                skipped,
+            } = workspace;
+            if let Some(policy) = policy {
+                policy.apply(index, &mut front.as_mut_slice()[..topology[index].neurons])?;
+            }
+            if skip::is_skipped(topology, index) {
+                let neurons = topology[index].neurons;
+                skipped[index]
+                    .rows_mut(0, neurons)
+                    .copy_from(&front.rows(0, neurons));
+            }
+
+            let shape = &topology[index + 1];
+            let mut width = topology[index].neurons;
+            if let Some(Skip { from, merge }) = shape.skip {
+                let extra = skipped[from].rows(0, topology[from].neurons);
+                match merge {
+                    Merge::Add => {
+                        let mut previous = front.rows_mut(0, width);
+                        previous += &extra;
+                    }
+                    Merge::Concat => front.rows_mut(width, extra.len()).copy_from(&extra),
+                }
+                width = merge.width(width, extra.len());
+            }
+
+            layer.propagate_into(&front.rows(0, width), &mut back.rows_mut(0, shape.neurons));
+            std::mem::swap(front, back);
+        }
+
+        let front = &mut workspace.front.as_mut_slice()[..outputs.len()];
+        if let Some(policy) = policy {
+            policy.apply(self.layers.len(), front)?;
+        }
+        outputs.copy_from_slice(front);
+        Ok(())
+    }
+
+    /// Propagates many input vectors at once, one per column of `inputs`.
+    /// Each feed-forward layer runs as a single matrix-matrix product, so a
+    /// population sharing one network, such as the birds of a champion
+    /// benchmark, is evaluated in one call.
+    ///
+    /// Columns are independent: recurrent layers step every column from their
+    /// current hidden state and leave that state untouched, and Hebbian layers
+    /// use their current weights without learning from the batch.
+    pub fn propagate_batch(&self, inputs: &DMatrix<T>) -> DMatrix<T> {
+        assert_eq!(inputs.nrows(), self.topology[0].neurons);
+        // the inputs are only copied if a skip connection reads them again;
+        // the first layer never has a skip so it can read them directly
+        let kept = if skip::is_skipped(&self.topology, 0) {
+            inputs.clone()
+        } else {
+            DMatrix::zeros(0, inputs.ncols())
+        };
+        let mut outputs = Vec::with_capacity(self.topology.len());
+        outputs.push(kept);
+        outputs.push(self.layers[0].propagate_batch(inputs, self.topology[1].neurons));
+        for (layer, shape) in self.layers[1..].iter().zip(&self.topology[2..]) {
+            let next =
+                layer.propagate_batch(&skip::layer_input(&outputs, shape.skip), shape.neurons);
+            outputs.push(next);
+        }
+        outputs.pop().unwrap()
+    }
+}
+
+// The count does not depend on the scalar type, so it lives on the default
+// `Network` and callers need no type annotation.
+impl Network {
+    /// Number of weights (biases included) a network of this shape holds,
+    /// `usize::MAX` if it is too large to build.
+    pub fn weight_count(topology: &[LayerTopology]) -> usize {
+        Self::checked_weight_count(topology).unwrap_or(usize::MAX)
+    }
+
+    fn checked_weight_count(topology: &[LayerTopology]) -> Result<usize, NetworkError> {
+        (1..topology.len()).try_fold(0, |total: usize, layer| {
+            let input = skip::input_topology(topology, layer);
+            NetworkLayer::<f32>::weight_count(&input, &topology[layer])
+                .and_then(|count| total.checked_add(count))
+                .filter(|&total| total <= MAX_WEIGHTS)
+                .ok_or(NetworkError::TopologyTooLarge { layer })
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{LayerTopology, Network};
-    use nalgebra::{dvector, matrix, vector, Vector};
+    use crate::{
+        Activation, Initializer, LayerKind, LayerTopology, Merge, Network, NetworkError,
+        NonFinitePolicy, Padding, Skip, Workspace, MAX_NEURONS,
+    };
+    use nalgebra::{dvector, matrix, vector, DMatrix, Vector};
     use rand::SeedableRng;
     use rand_chacha::ChaCha8Rng;
 
     #[test]
     fn test_random_lifecycle() {
         let layers = &[
-            LayerTopology { neurons: 3 },
-            LayerTopology { neurons: 4 },
-            LayerTopology { neurons: 1 },
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::LeakyReLU(0.01),
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Tanh,
+                ..Default::default()
+            },
         ];
         let mut rng = ChaCha8Rng::from_seed(Default::default());
-        let network = Network::random(&mut rng, layers);
-        let second_network = Network::from_weights(layers, &mut network.weights());
+        let network: Network = Network::random(&mut rng, layers);
+        let second_network = Network::from_weights(layers, network.weights());
 
         assert_eq!(network.layers, second_network.layers);
     }
 
+    #[test]
+    fn test_cast_round_trip() {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Tanh,
+                kind: LayerKind::Gru,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Sigmoid,
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network: Network = Network::random(&mut rng, layers);
+        network.propagate(vec![0.5, -0.5, 1.0]);
+
+        let mut wide = network.cast::<f64>();
+        assert!(network
+            .weights()
+            .zip(wide.weights())
+            .all(|(narrow, wide)| f64::from(narrow) == wide));
+
+        let mut back = wide.cast::<f32>();
+        assert!(network.weights().eq(back.weights()));
+
+        let inputs = vec![0.1, 0.2, -0.3];
+        let narrow = network.propagate(inputs.clone());
+        let wide = wide.propagate(inputs.iter().map(|&x| f64::from(x)).collect());
+        assert_eq!(narrow, back.propagate(inputs));
+        for (narrow, wide) in narrow.iter().zip(&wide) {
+            assert!((f64::from(*narrow) - wide).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_initializer_per_layer() {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                initializer: Initializer::He,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                initializer: Initializer::Zeros,
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network: Network = Network::random(&mut rng, layers);
+        let weights: Vec<_> = network.weights().collect();
+        let (hidden, output) = weights.split_at(4 * 4);
+
+        // He leaves the biases (first of every row) at zero but not the weights
+        assert!(hidden.chunks(4).all(|row| row[0] == 0.0));
+        assert!(hidden.iter().any(|&w| w != 0.0));
+        assert!(output.iter().all(|&w| w == 0.0));
+    }
+
     #[test]
     fn test_propagate() {
         let actual_weights: nalgebra::Matrix<f32, _, _, _> = matrix![2.0, 3.0; 4.0, 5.0; 6.0, 7.0];
@@ -96,13 +735,15 @@ mod tests {
         let layers = &[
             LayerTopology {
                 neurons: input_neurons,
+                ..Default::default()
             },
             LayerTopology {
                 neurons: output_neurons,
+                ..Default::default()
             },
         ];
 
-        let network = Network::from_weights(layers, neuron_weights);
+        let mut network = Network::from_weights(layers, neuron_weights);
 
         let expected_result = result.into_iter().cloned().collect::<Vec<f32>>();
         let actual_result = network.propagate(input_vector.data.as_vec().to_owned());
@@ -120,11 +761,733 @@ mod tests {
         let layers = &[
             LayerTopology {
                 neurons: input_neurons,
+                ..Default::default()
             },
             LayerTopology {
                 neurons: output_neurons,
+                ..Default::default()
             },
         ];
         Network::from_weights(layers, neuron_weights);
     }
+
+    #[test]
+    fn test_prune_lifecycle() {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Tanh,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, layers);
+        let inputs = vec![0.3, -0.2, 0.9];
+
+        let pruned = network.prune(0.5);
+        let weights: Vec<_> = network.weights().collect();
+
+        assert!(pruned > 0);
+        assert_eq!(weights.len(), Network::weight_count(layers) - pruned);
+        assert_eq!(weights.len(), Network::weight_count(network.topology()));
+        assert!(network.topology()[1].mask.is_some());
+
+        let mut second_network = Network::from_weights(network.topology(), weights);
+        assert_eq!(network.layers, second_network.layers);
+        assert_eq!(
+            network.propagate(inputs.clone()),
+            second_network.propagate(inputs)
+        );
+    }
+
+    #[test]
+    fn test_mask_errors() {
+        let mut layers = vec![
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                mask: Some(vec![true, false, true]),
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).err(),
+            Some(NetworkError::MaskSize {
+                layer: 1,
+                expected: 4,
+                received: 3
+            })
+        );
+
+        layers[1].mask = Some(vec![true; 4]);
+        layers[1].kind = LayerKind::Elman;
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).err(),
+            Some(NetworkError::UnsupportedLayer {
+                layer: 1,
+                kind: LayerKind::Elman
+            })
+        );
+    }
+
+    fn conv_topology() -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 9,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 27,
+                activation: Activation::ReLU,
+                kind: LayerKind::Conv1d {
+                    channels: 3,
+                    kernel: 3,
+                    stride: 1,
+                    padding: Padding::Circular,
+                },
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Tanh,
+                kind: LayerKind::Conv1d {
+                    channels: 2,
+                    kernel: 5,
+                    stride: 3,
+                    padding: Padding::Valid,
+                },
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_conv_lifecycle() {
+        let layers = conv_topology();
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network: Network = Network::random(&mut rng, &layers);
+        let weights: Vec<_> = network.weights().collect();
+
+        assert_eq!(weights.len(), Network::weight_count(&layers));
+        assert_eq!(weights.len(), 3 * 4 + 2 * (3 * 5 + 1) + 2 * 5);
+        let mut second_network = Network::from_weights(&layers, weights.clone());
+        assert_eq!(second_network.weights().collect::<Vec<_>>(), weights);
+
+        let inputs: Vec<f32> = (0..9).map(|i| (i as f32 * 0.7).sin()).collect();
+        let outputs = network.propagate(inputs.clone());
+        let mut workspace = network.workspace();
+        let mut into_outputs = vec![0.0; 2];
+        second_network.propagate_into(&inputs, &mut into_outputs, &mut workspace);
+        let batch = network.propagate_batch(&DMatrix::from_column_slice(9, 1, &inputs));
+
+        assert_eq!(outputs, into_outputs);
+        assert_eq!(outputs, batch.as_slice());
+    }
+
+    #[test]
+    fn test_conv_errors() {
+        let mut layers = conv_topology();
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        layers[2].neurons = 6;
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).err(),
+            Some(NetworkError::ConvolutionSize {
+                layer: 2,
+                expected: 4,
+                received: 6
+            })
+        );
+
+        layers[2].kind = LayerKind::Conv1d {
+            channels: 2,
+            kernel: 10,
+            stride: 1,
+            padding: Padding::Valid,
+        };
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).err(),
+            Some(NetworkError::InvalidConvolution { layer: 2 })
+        );
+
+        layers[0].neurons = 10;
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).err(),
+            Some(NetworkError::ConvolutionSize {
+                layer: 1,
+                expected: 30,
+                received: 27
+            })
+        );
+    }
+
+    fn skip_topology(merge: Merge) -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                skip: Some(Skip { from: 0, merge }),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_skip_merges() {
+        // h = 2x, then 3 (h + x)
+        let mut network: Network =
+            Network::from_weights(&skip_topology(Merge::Add), vec![0.0, 2.0, 0.0, 3.0]);
+        assert_eq!(network.propagate(vec![1.0]), [9.0]);
+
+        // h = 2x, then 3h + 5x
+        let layers = skip_topology(Merge::Concat);
+        assert_eq!(Network::weight_count(&layers), 5);
+        let mut network: Network = Network::from_weights(&layers, vec![0.0, 2.0, 0.0, 3.0, 5.0]);
+        assert_eq!(network.propagate_traced(vec![1.0]), [[1.0], [2.0], [11.0]]);
+    }
+
+    #[test]
+    fn test_skip_lifecycle() {
+        let mut layers = conv_topology();
+        layers[3].neurons = 4;
+        layers[3].skip = Some(Skip {
+            from: 1,
+            merge: Merge::Concat,
+        });
+        layers.push(LayerTopology {
+            neurons: 2,
+            activation: Activation::Sigmoid,
+            skip: Some(Skip {
+                from: 2,
+                merge: Merge::Add,
+            }),
+            mask: Some((0..8).map(|i| i != 5).collect()),
+            ..Default::default()
+        });
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network: Network = Network::random(&mut rng, &layers);
+        let weights: Vec<_> = network.weights().collect();
+
+        // layer 3 reads the 4 convolution outputs and the 27 skipped ones
+        assert_eq!(
+            weights.len(),
+            3 * 4 + 2 * (3 * 5 + 1) + 4 * (4 + 27 + 1) + (2 + 7)
+        );
+        assert_eq!(weights.len(), Network::weight_count(&layers));
+        let mut second_network = Network::from_weights(&layers, weights.clone());
+        assert_eq!(second_network.weights().collect::<Vec<_>>(), weights);
+
+        let inputs: Vec<f32> = (0..9).map(|i| (i as f32 * 0.7).cos()).collect();
+        let outputs = network.propagate(inputs.clone());
+        let mut workspace = Workspace::default();
+        let mut into_outputs = vec![0.0; 2];
+        second_network.propagate_into(&inputs, &mut into_outputs, &mut workspace);
+        let batch = network.propagate_batch(&DMatrix::from_column_slice(9, 1, &inputs));
+
+        assert_eq!(outputs, into_outputs);
+        assert_eq!(outputs, batch.as_slice());
+    }
+
+    #[test]
+    fn test_skip_errors() {
+        let mut layers = skip_topology(Merge::Add);
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+        layers[2].skip = Some(Skip {
+            from: 1,
+            merge: Merge::Add,
+        });
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).err(),
+            Some(NetworkError::InvalidSkip { layer: 2, from: 1 })
+        );
+
+        layers[2].skip = Some(Skip {
+            from: 0,
+            merge: Merge::Add,
+        });
+        layers[1].neurons = 3;
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).err(),
+            Some(NetworkError::SkipSize {
+                layer: 2,
+                expected: 3,
+                received: 1
+            })
+        );
+
+        // a convolution would read the skipped values as channels
+        let mut layers = conv_topology();
+        layers[2].skip = Some(Skip {
+            from: 0,
+            merge: Merge::Concat,
+        });
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).err(),
+            Some(NetworkError::InvalidSkip { layer: 2, from: 0 })
+        );
+
+        let network = Network::<f32>::random(&mut rng, &skip_topology(Merge::Concat));
+        assert_eq!(
+            network.to_rust(),
+            Err(NetworkError::UnsupportedSkip { layer: 2 })
+        );
+    }
+
+    #[test]
+    fn test_recurrent_network_is_sync() {
+        fn assert_sync<T: Send + Sync>(_: &T) {}
+
+        let mut layers = skip_topology(Merge::Add);
+        layers[1].kind = LayerKind::Gru;
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        assert_sync(&Network::<f32>::random(&mut rng, &layers));
+    }
+
+    #[test]
+    fn test_hebbian_lifetime() {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Tanh,
+                kind: LayerKind::Hebbian,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network: Network = Network::random(&mut rng, layers);
+        let chromosome: Vec<_> = network.weights().collect();
+        assert_eq!(chromosome.len(), 4 * 4 + 5 + 5);
+
+        let inputs = vec![0.5, -0.25, 1.0];
+        let first = network.propagate(inputs.clone());
+        for _ in 0..10 {
+            network.propagate(inputs.clone());
+        }
+        assert_ne!(network.propagate(inputs.clone()), first);
+        assert_eq!(network.weights().collect::<Vec<_>>(), chromosome);
+
+        // a network bred from the chromosome starts from the evolved weights
+        let mut offspring = Network::from_weights(layers, chromosome);
+        assert_eq!(offspring.propagate(inputs.clone()), first);
+
+        network.reset_state();
+        assert_eq!(network.propagate(inputs), first);
+    }
+
+    #[test]
+    fn test_propagate_traced() {
+        let layers = &[
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::ReLU,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                ..Default::default()
+            },
+        ];
+        // hidden = relu([x0 - x1, x1 - x0]), output = h0 + 2 h1 + 0.5
+        let weights = vec![0.0, 1.0, -1.0, 0.0, -1.0, 1.0, 0.5, 1.0, 2.0];
+        let mut network = Network::from_weights(layers, weights);
+
+        let trace = network.propagate_traced(vec![1.0, 3.0]);
+
+        assert_eq!(trace, vec![vec![1.0, 3.0], vec![0.0, 2.0], vec![4.5]]);
+        assert_eq!(trace[2], network.propagate(vec![1.0, 3.0]));
+    }
+
+    #[test]
+    fn test_try_propagate() {
+        let layers = &[
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::ReLU,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                ..Default::default()
+            },
+        ];
+        // hidden = relu([1e30 (x0 - x1), 1e30 (x1 - x0)]), output overflows
+        let weights = vec![0.0, 1e30, -1e30, 0.0, -1e30, 1e30, 0.5, 1e30, 1e30];
+        let mut network: Network = Network::from_weights(layers, weights);
+
+        assert_eq!(network.propagate(vec![1.0, 3.0]), vec![f32::INFINITY]);
+        assert_eq!(
+            network.try_propagate(vec![1.0, 3.0], NonFinitePolicy::Error),
+            Err(NetworkError::NonFiniteActivation {
+                layer: 2,
+                neuron: 0,
+                value: f64::INFINITY
+            })
+        );
+        assert!(matches!(
+            network.try_propagate(vec![f32::NAN, 3.0], NonFinitePolicy::Error),
+            Err(NetworkError::NonFiniteActivation {
+                layer: 0,
+                neuron: 0,
+                value
+            }) if value.is_nan()
+        ));
+        assert_eq!(
+            network.try_propagate_traced(vec![1.0, 3.0], NonFinitePolicy::Clamp { limit: 10.0 }),
+            Ok(vec![vec![1.0, 3.0], vec![0.0, 10.0], vec![10.0]])
+        );
+        assert_eq!(
+            network.try_propagate(vec![1.0, 3.0], NonFinitePolicy::Zero),
+            Ok(vec![0.0])
+        );
+
+        let mut workspace = network.workspace();
+        let mut output = [0.0];
+        let policy = NonFinitePolicy::Clamp { limit: 10.0 };
+        assert_eq!(
+            network.try_propagate_into(&[1.0, 3.0], &mut output, &mut workspace, policy),
+            Ok(())
+        );
+        assert_eq!(output, [10.0]);
+        assert_eq!(
+            network.try_propagate_into(
+                &[1.0, 3.0],
+                &mut output,
+                &mut workspace,
+                NonFinitePolicy::Error
+            ),
+            Err(NetworkError::NonFiniteActivation {
+                layer: 2,
+                neuron: 0,
+                value: f64::INFINITY
+            })
+        );
+    }
+
+    #[test]
+    fn test_propagate_batch() {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 5,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, layers);
+        let inputs = DMatrix::from_fn(3, 40, |row, col| (row * col) as f32 / 40.0 - 1.0);
+
+        let outputs = network.propagate_batch(&inputs);
+
+        assert_eq!((outputs.nrows(), outputs.ncols()), (2, 40));
+        for (input, output) in inputs.column_iter().zip(outputs.column_iter()) {
+            let expected = network.propagate(input.iter().cloned().collect());
+            assert_eq!(expected, output.iter().cloned().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_propagate_into() {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 6,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Softsign,
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, layers);
+        let mut workspace = Workspace::default();
+        let mut outputs = [0.0; 2];
+
+        network.propagate_into(&[0.1, 0.2, 0.3], &mut outputs, &mut workspace);
+        assert_eq!(network.propagate(vec![0.1, 0.2, 0.3]), outputs);
+
+        let buffers = (workspace.front.as_ptr(), workspace.back.as_ptr());
+        network.propagate_into(&[-0.4, 0.0, 0.9], &mut outputs, &mut workspace);
+        assert_eq!(network.propagate(vec![-0.4, 0.0, 0.9]), outputs);
+        assert_eq!(buffers, (workspace.front.as_ptr(), workspace.back.as_ptr()));
+    }
+
+    #[test]
+    fn test_recurrent_lifecycle() {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Tanh,
+                kind: LayerKind::Gru,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                kind: LayerKind::Elman,
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, layers);
+        let mut second_network = Network::from_weights(layers, network.weights());
+
+        assert_eq!(network.weights().count(), Network::weight_count(layers));
+        assert_eq!(network.layers, second_network.layers);
+
+        let first = network.propagate(vec![1.0, 0.0, -1.0]);
+        let second = network.propagate(vec![1.0, 0.0, -1.0]);
+        assert_ne!(first, second);
+
+        network.reset_state();
+        assert_eq!(first, network.propagate(vec![1.0, 0.0, -1.0]));
+        assert_eq!(first, second_network.propagate(vec![1.0, 0.0, -1.0]));
+    }
+
+    #[test]
+    fn test_elman_memory() {
+        let layers = &[
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Identity,
+                kind: LayerKind::Elman,
+                ..Default::default()
+            },
+        ];
+        // h' = x + h
+        let mut network = Network::from_weights(layers, vec![0.0, 1.0, 1.0]);
+        let mut workspace = network.workspace();
+        let mut outputs = [0.0];
+
+        assert_eq!(network.propagate(vec![1.0]), vec![1.0]);
+        network.propagate_into(&[0.0], &mut outputs, &mut workspace);
+        assert_eq!(outputs, [1.0]);
+        assert_eq!(
+            network.propagate_batch(&DMatrix::from_row_slice(1, 2, &[0.0, 2.0])),
+            DMatrix::from_row_slice(1, 2, &[1.0, 3.0])
+        );
+        assert_eq!(network.propagate(vec![0.5]), vec![1.5]);
+    }
+
+    #[test]
+    fn test_try_from_weights_errors() {
+        let layers = &[
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(Network::weight_count(layers), 9);
+
+        assert_eq!(
+            Network::try_from_weights(layers, vec![1.0; 8]).unwrap_err(),
+            NetworkError::TooFewWeights {
+                expected: 9,
+                received: 8
+            }
+        );
+        assert_eq!(
+            Network::try_from_weights(layers, vec![1.0; 10]).unwrap_err(),
+            NetworkError::TooManyWeights {
+                expected: 9,
+                received: 10
+            }
+        );
+        assert_eq!(
+            Network::try_from_weights(layers, std::iter::repeat(1.0)).unwrap_err(),
+            NetworkError::TooManyWeights {
+                expected: 9,
+                received: 10
+            }
+        );
+
+        let mut weights = vec![1.0; 9];
+        weights[4] = f32::NAN;
+        assert!(matches!(
+            Network::try_from_weights(layers, weights),
+            Err(NetworkError::NonFiniteWeight { index: 4, .. })
+        ));
+
+        assert_eq!(
+            Network::<f32>::try_from_weights(&layers[..1], vec![]).unwrap_err(),
+            NetworkError::EmptyTopology
+        );
+    }
+
+    #[test]
+    fn test_topology_too_large() {
+        let mut layers = vec![
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: usize::MAX,
+                ..Default::default()
+            },
+        ];
+        let error = Some(NetworkError::TopologyTooLarge { layer: 1 });
+        assert_eq!(Network::weight_count(&layers), usize::MAX);
+        assert_eq!(
+            Network::<f32>::try_from_weights(&layers, vec![]).err(),
+            error
+        );
+
+        // each layer is allowed, but together they need too many weights
+        layers[0].neurons = MAX_NEURONS;
+        layers[1].neurons = MAX_NEURONS;
+        assert_eq!(
+            Network::<f32>::try_from_weights(&layers, vec![]).err(),
+            error
+        );
+
+        layers[1].neurons = 4;
+        layers[1].kind = LayerKind::Conv1d {
+            channels: 1,
+            kernel: usize::MAX,
+            stride: 1,
+            padding: Padding::Circular,
+        };
+        assert_eq!(
+            Network::<f32>::try_from_weights(&layers, vec![]).err(),
+            error
+        );
+    }
+
+    #[test]
+    fn test_try_random_errors() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut layers = vec![
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 0,
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &layers).unwrap_err(),
+            NetworkError::ZeroWidthLayer { layer: 1 }
+        );
+        assert_eq!(
+            Network::<f32>::try_random(&mut rng, &[]).unwrap_err(),
+            NetworkError::EmptyTopology
+        );
+
+        layers[1].neurons = 1;
+        for initializer in [
+            Initializer::Uniform {
+                low: 1.0,
+                high: 1.0,
+            },
+            Initializer::Uniform {
+                low: f32::NEG_INFINITY,
+                high: 1.0,
+            },
+            Initializer::Normal {
+                mean: 0.0,
+                std_dev: -1.0,
+            },
+            Initializer::Normal {
+                mean: 0.0,
+                std_dev: f32::NAN,
+            },
+        ] {
+            layers[1].initializer = initializer;
+            assert!(matches!(
+                Network::<f32>::try_random(&mut rng, &layers),
+                Err(NetworkError::InvalidInitializer { layer: 1, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_propagate_signed_output() {
+        let layers = &[
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Identity,
+                ..Default::default()
+            },
+        ];
+        let mut network = Network::from_weights(layers, vec![0.0, -1.0, 0.0, 1.0]);
+
+        assert_eq!(network.propagate(vec![3.0]), vec![-3.0, 3.0]);
+    }
 }
@@ -0,0 +1,460 @@
+use std::collections::{HashMap, HashSet};
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use super::{Innovations, NeatConfig, NeatNetwork};
+use crate::Activation;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Input,
+    Hidden,
+    Output,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NodeGene {
+    pub id: usize,
+    pub kind: NodeKind,
+    pub bias: f32,
+    pub activation: Activation,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConnectionGene {
+    pub innovation: usize,
+    pub from: usize,
+    pub to: usize,
+    pub weight: f32,
+    pub enabled: bool,
+}
+
+/// Node genes sorted by id and connection genes sorted by innovation number.
+/// Input nodes use ids `0..inputs`, output nodes the following `outputs` ids.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Genome {
+    nodes: Vec<NodeGene>,
+    connections: Vec<ConnectionGene>,
+}
+
+impl Genome {
+    /// Every input connected straight to every output.
+    pub fn minimal(
+        config: &NeatConfig,
+        innovations: &mut Innovations,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        let inputs = (0..config.inputs).map(|id| NodeGene {
+            id,
+            kind: NodeKind::Input,
+            bias: 0.0,
+            activation: Activation::Identity,
+        });
+        let outputs = (config.inputs..config.inputs + config.outputs).map(|id| NodeGene {
+            id,
+            kind: NodeKind::Output,
+            bias: rng.gen_range(-1.0..=1.0),
+            activation: config.output_activation,
+        });
+        let nodes = inputs.chain(outputs).collect();
+
+        let mut connections = Vec::new();
+        for from in 0..config.inputs {
+            for to in config.inputs..config.inputs + config.outputs {
+                connections.push(ConnectionGene {
+                    innovation: innovations.connection(from, to),
+                    from,
+                    to,
+                    weight: rng.gen_range(-1.0..=1.0),
+                    enabled: true,
+                });
+            }
+        }
+        connections.sort_by_key(|connection| connection.innovation);
+
+        Self { nodes, connections }
+    }
+
+    pub fn nodes(&self) -> &[NodeGene] {
+        &self.nodes
+    }
+
+    pub fn connections(&self) -> &[ConnectionGene] {
+        &self.connections
+    }
+
+    pub fn network(&self) -> NeatNetwork {
+        NeatNetwork::new(self)
+    }
+
+    pub fn mutate(
+        &mut self,
+        config: &NeatConfig,
+        innovations: &mut Innovations,
+        rng: &mut dyn RngCore,
+    ) {
+        if rng.gen_bool(config.add_node_rate as _) {
+            self.mutate_add_node(config, innovations, rng);
+        }
+        if rng.gen_bool(config.add_connection_rate as _) {
+            self.mutate_add_connection(innovations, rng);
+        }
+        if rng.gen_bool(config.toggle_rate as _) {
+            self.mutate_toggle(rng);
+        }
+        if rng.gen_bool(config.weight_mutation_rate as _) {
+            self.mutate_weights(config, rng);
+        }
+    }
+
+    pub fn mutate_weights(&mut self, config: &NeatConfig, rng: &mut dyn RngCore) {
+        let mut perturb = |value: &mut f32| {
+            if rng.gen_bool(config.weight_replace_rate as _) {
+                *value = rng.gen_range(-1.0..=1.0);
+            } else {
+                *value += rng.gen_range(-config.weight_power..=config.weight_power);
+            }
+        };
+        for connection in &mut self.connections {
+            perturb(&mut connection.weight);
+        }
+        for node in &mut self.nodes {
+            if node.kind != NodeKind::Input {
+                perturb(&mut node.bias);
+            }
+        }
+    }
+
+    /// Connects two previously unconnected nodes without creating a cycle.
+    /// Returns whether a connection was added.
+    pub fn mutate_add_connection(
+        &mut self,
+        innovations: &mut Innovations,
+        rng: &mut dyn RngCore,
+    ) -> bool {
+        let existing: HashSet<_> = self.connections.iter().map(|c| (c.from, c.to)).collect();
+        let candidates: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|from| from.kind != NodeKind::Output)
+            .flat_map(|from| {
+                self.nodes
+                    .iter()
+                    .filter(|to| to.kind != NodeKind::Input && to.id != from.id)
+                    .map(move |to| (from.id, to.id))
+            })
+            .filter(|pair| !existing.contains(pair))
+            .filter(|&(from, to)| !self.creates_cycle(from, to))
+            .collect();
+
+        match candidates.choose(rng) {
+            Some(&(from, to)) => {
+                self.insert_connection(ConnectionGene {
+                    innovation: innovations.connection(from, to),
+                    from,
+                    to,
+                    weight: rng.gen_range(-1.0..=1.0),
+                    enabled: true,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Splits an enabled connection `a -> b` into `a -> new -> b`. The incoming
+    /// connection gets weight 1 and the outgoing one keeps the old weight.
+    /// Returns whether a node was added.
+    pub fn mutate_add_node(
+        &mut self,
+        config: &NeatConfig,
+        innovations: &mut Innovations,
+        rng: &mut dyn RngCore,
+    ) -> bool {
+        let enabled: Vec<_> = (0..self.connections.len())
+            .filter(|&index| self.connections[index].enabled)
+            .collect();
+        let index = match enabled.choose(rng) {
+            Some(&index) => index,
+            None => return false,
+        };
+
+        let split = &mut self.connections[index];
+        split.enabled = false;
+        let (innovation, from, to, weight) = (split.innovation, split.from, split.to, split.weight);
+
+        let mut node = innovations.split(innovation);
+        if self.node(node).is_some() {
+            node = innovations.fresh_node();
+        }
+        let position = self.nodes.partition_point(|gene| gene.id < node);
+        self.nodes.insert(
+            position,
+            NodeGene {
+                id: node,
+                kind: NodeKind::Hidden,
+                bias: 0.0,
+                activation: config.hidden_activation,
+            },
+        );
+
+        self.insert_connection(ConnectionGene {
+            innovation: innovations.connection(from, node),
+            from,
+            to: node,
+            weight: 1.0,
+            enabled: true,
+        });
+        self.insert_connection(ConnectionGene {
+            innovation: innovations.connection(node, to),
+            from: node,
+            to,
+            weight,
+            enabled: true,
+        });
+        true
+    }
+
+    /// Flips a random connection. Connections that would close a cycle stay
+    /// disabled.
+    pub fn mutate_toggle(&mut self, rng: &mut dyn RngCore) {
+        let index = match (0..self.connections.len()).collect::<Vec<_>>().choose(rng) {
+            Some(&index) => index,
+            None => return,
+        };
+        let (from, to, enabled) = {
+            let connection = &self.connections[index];
+            (connection.from, connection.to, connection.enabled)
+        };
+        if enabled || !self.creates_cycle(from, to) {
+            self.connections[index].enabled = !enabled;
+        }
+    }
+
+    /// Child of two parents. Matching genes are inherited at random, disjoint
+    /// and excess genes come from `fitter`, so the child shares its topology.
+    pub fn crossover(fitter: &Genome, other: &Genome, rng: &mut dyn RngCore) -> Genome {
+        let other_genes: HashMap<_, _> = other
+            .connections
+            .iter()
+            .map(|connection| (connection.innovation, connection))
+            .collect();
+
+        let connections = fitter
+            .connections
+            .iter()
+            .map(|gene| match other_genes.get(&gene.innovation) {
+                Some(&matching) => {
+                    let mut child = if rng.gen_bool(0.5) {
+                        gene.clone()
+                    } else {
+                        matching.clone()
+                    };
+                    child.enabled = if gene.enabled && matching.enabled {
+                        true
+                    } else {
+                        !rng.gen_bool(0.75)
+                    };
+                    child
+                }
+                None => gene.clone(),
+            })
+            .collect();
+
+        let mut child = Genome {
+            nodes: fitter.nodes.clone(),
+            connections,
+        };
+        // re-enabling a gene can close a cycle through genes only `fitter` has
+        for index in 0..child.connections.len() {
+            let (from, to) = (child.connections[index].from, child.connections[index].to);
+            if child.connections[index].enabled {
+                child.connections[index].enabled = false;
+                child.connections[index].enabled = !child.creates_cycle(from, to);
+            }
+        }
+        child
+    }
+
+    /// Compatibility distance `c1 * E / N + c2 * D / N + c3 * W`, where `E` and
+    /// `D` count excess and disjoint genes and `W` is the mean weight
+    /// difference of matching genes.
+    pub fn distance(&self, other: &Genome, config: &NeatConfig) -> f32 {
+        let (a, b) = (&self.connections, &other.connections);
+        let (mut i, mut j) = (0, 0);
+        let (mut disjoint, mut matching, mut weight_difference) = (0, 0, 0.0);
+
+        while i < a.len() && j < b.len() {
+            match a[i].innovation.cmp(&b[j].innovation) {
+                std::cmp::Ordering::Equal => {
+                    matching += 1;
+                    weight_difference += (a[i].weight - b[j].weight).abs();
+                    i += 1;
+                    j += 1;
+                }
+                std::cmp::Ordering::Less => {
+                    disjoint += 1;
+                    i += 1;
+                }
+                std::cmp::Ordering::Greater => {
+                    disjoint += 1;
+                    j += 1;
+                }
+            }
+        }
+        let excess = (a.len() - i) + (b.len() - j);
+        let genes = a.len().max(b.len()).max(1) as f32;
+        let weight_difference = if matching > 0 {
+            weight_difference / matching as f32
+        } else {
+            0.0
+        };
+
+        config.excess_coefficient * excess as f32 / genes
+            + config.disjoint_coefficient * disjoint as f32 / genes
+            + config.weight_coefficient * weight_difference
+    }
+
+    pub(crate) fn node(&self, id: usize) -> Option<&NodeGene> {
+        self.nodes
+            .binary_search_by_key(&id, |node| node.id)
+            .ok()
+            .map(|index| &self.nodes[index])
+    }
+
+    /// Whether an enabled `from -> to` connection would close a cycle, i.e.
+    /// `from` is already reachable from `to`.
+    fn creates_cycle(&self, from: usize, to: usize) -> bool {
+        if from == to {
+            return true;
+        }
+        let mut visited = HashSet::new();
+        let mut stack = vec![to];
+        while let Some(node) = stack.pop() {
+            if node == from {
+                return true;
+            }
+            if !visited.insert(node) {
+                continue;
+            }
+            stack.extend(
+                self.connections
+                    .iter()
+                    .filter(|connection| connection.enabled && connection.from == node)
+                    .map(|connection| connection.to),
+            );
+        }
+        false
+    }
+
+    fn insert_connection(&mut self, connection: ConnectionGene) {
+        let position = self
+            .connections
+            .partition_point(|gene| gene.innovation < connection.innovation);
+        self.connections.insert(position, connection);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Genome, NodeKind};
+    use crate::neat::{Innovations, NeatConfig};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn setup() -> (NeatConfig, Innovations, ChaCha8Rng) {
+        let config = NeatConfig::new(3, 2);
+        let innovations = Innovations::new(5);
+        (
+            config,
+            innovations,
+            ChaCha8Rng::from_seed(Default::default()),
+        )
+    }
+
+    #[test]
+    fn test_minimal() {
+        let (config, mut innovations, mut rng) = setup();
+        let genome = Genome::minimal(&config, &mut innovations, &mut rng);
+
+        assert_eq!(genome.nodes().len(), 5);
+        assert_eq!(genome.connections().len(), 6);
+        assert!(genome
+            .connections()
+            .windows(2)
+            .all(|pair| pair[0].innovation < pair[1].innovation));
+    }
+
+    #[test]
+    fn test_add_node_splits_connection() {
+        let (config, mut innovations, mut rng) = setup();
+        let mut genome = Genome::minimal(&config, &mut innovations, &mut rng);
+
+        assert!(genome.mutate_add_node(&config, &mut innovations, &mut rng));
+
+        let hidden = genome.node(5).unwrap();
+        assert_eq!(hidden.kind, NodeKind::Hidden);
+        assert_eq!(genome.connections().len(), 8);
+        assert_eq!(
+            genome.connections().iter().filter(|c| !c.enabled).count(),
+            1
+        );
+        let incoming = genome.connections().iter().find(|c| c.to == 5).unwrap();
+        assert_eq!(incoming.weight, 1.0);
+    }
+
+    #[test]
+    fn test_structural_mutations_stay_acyclic() {
+        let (config, mut innovations, mut rng) = setup();
+        let mut genome = Genome::minimal(&config, &mut innovations, &mut rng);
+
+        for _ in 0..50 {
+            genome.mutate_add_node(&config, &mut innovations, &mut rng);
+            genome.mutate_add_connection(&mut innovations, &mut rng);
+            genome.mutate_toggle(&mut rng);
+        }
+
+        // NeatNetwork::new panics on cycles
+        let mut network = genome.network();
+        assert_eq!(network.propagate(&[0.5, -0.5, 1.0]).len(), 2);
+    }
+
+    #[test]
+    fn test_same_mutation_same_innovation() {
+        let (config, mut innovations, mut rng) = setup();
+        let genome = Genome::minimal(&config, &mut innovations, &mut rng);
+        let (mut a, mut b) = (genome.clone(), genome);
+
+        let mut split_rng = ChaCha8Rng::from_seed([1; 32]);
+        a.mutate_add_node(&config, &mut innovations, &mut split_rng);
+        let mut split_rng = ChaCha8Rng::from_seed([1; 32]);
+        b.mutate_add_node(&config, &mut innovations, &mut split_rng);
+
+        assert_eq!(a, b);
+        assert_eq!(a.distance(&b, &config), 0.0);
+    }
+
+    #[test]
+    fn test_crossover_follows_fitter_parent() {
+        let (config, mut innovations, mut rng) = setup();
+        let base = Genome::minimal(&config, &mut innovations, &mut rng);
+        let mut fitter = base.clone();
+        fitter.mutate_add_node(&config, &mut innovations, &mut rng);
+        let mut other = base;
+        other.mutate_add_connection(&mut innovations, &mut rng);
+        other.mutate_weights(&config, &mut rng);
+
+        let child = Genome::crossover(&fitter, &other, &mut rng);
+
+        let innovations = |genome: &Genome| {
+            genome
+                .connections()
+                .iter()
+                .map(|c| c.innovation)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(innovations(&child), innovations(&fitter));
+        assert_eq!(child.nodes(), fitter.nodes());
+        assert!(fitter.distance(&other, &config) > 0.0);
+    }
+}
@@ -0,0 +1,62 @@
+use std::collections::HashMap;
+
+/// Historical markings shared by every genome of a run. The same structural
+/// mutation always receives the same number, no matter which genome makes it.
+#[derive(Clone, Debug)]
+pub struct Innovations {
+    connections: HashMap<(usize, usize), usize>,
+    splits: HashMap<usize, usize>,
+    next_node: usize,
+}
+
+impl Innovations {
+    /// `nodes` is the number of input and output nodes every genome starts with.
+    pub fn new(nodes: usize) -> Self {
+        Self {
+            connections: HashMap::new(),
+            splits: HashMap::new(),
+            next_node: nodes,
+        }
+    }
+
+    /// Innovation number of the connection `from -> to`.
+    pub fn connection(&mut self, from: usize, to: usize) -> usize {
+        let next = self.connections.len();
+        *self.connections.entry((from, to)).or_insert(next)
+    }
+
+    /// Id of the node created by splitting connection `innovation`.
+    pub fn split(&mut self, innovation: usize) -> usize {
+        let next = self.next_node;
+        let node = *self.splits.entry(innovation).or_insert(next);
+        if node == next {
+            self.next_node += 1;
+        }
+        node
+    }
+
+    /// Id for a node that has no shared history.
+    pub fn fresh_node(&mut self) -> usize {
+        self.next_node += 1;
+        self.next_node - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Innovations;
+
+    #[test]
+    fn test_markings_are_shared() {
+        let mut innovations = Innovations::new(3);
+
+        assert_eq!(innovations.connection(0, 2), 0);
+        assert_eq!(innovations.connection(1, 2), 1);
+        assert_eq!(innovations.connection(0, 2), 0);
+
+        assert_eq!(innovations.split(1), 3);
+        assert_eq!(innovations.split(0), 4);
+        assert_eq!(innovations.split(1), 3);
+        assert_eq!(innovations.fresh_node(), 5);
+    }
+}
@@ -0,0 +1,17 @@
+//! NeuroEvolution of Augmenting Topologies.
+//!
+//! A [`Genome`] is a graph of node and connection genes. Every structural
+//! change is tagged with an innovation number handed out by a shared
+//! [`Innovations`] record, which lets crossover line up genes from parents
+//! with different topologies and lets [`Population`] group similar genomes
+//! into [`Species`].
+
+mod genome;
+mod innovation;
+mod network;
+mod population;
+
+pub use genome::{ConnectionGene, Genome, NodeGene, NodeKind};
+pub use innovation::Innovations;
+pub use network::NeatNetwork;
+pub use population::{NeatConfig, Population, Species};
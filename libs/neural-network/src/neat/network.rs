@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use super::{Genome, NodeKind};
+use crate::Activation;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Neuron {
+    index: usize,
+    bias: f32,
+    activation: Activation,
+    incoming: Vec<(usize, f32)>,
+}
+
+/// Phenotype of a [`Genome`]: its enabled connections evaluated in
+/// topological order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NeatNetwork {
+    inputs: Vec<usize>,
+    outputs: Vec<usize>,
+    neurons: Vec<Neuron>,
+    values: Vec<f32>,
+}
+
+impl NeatNetwork {
+    /// Panics if the enabled connections of `genome` contain a cycle.
+    pub fn new(genome: &Genome) -> Self {
+        let nodes = genome.nodes();
+        let index: HashMap<_, _> = nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id, index))
+            .collect();
+
+        let mut incoming = vec![Vec::new(); nodes.len()];
+        let mut outgoing = vec![Vec::new(); nodes.len()];
+        let mut pending = vec![0; nodes.len()];
+        for connection in genome.connections().iter().filter(|c| c.enabled) {
+            let (from, to) = (index[&connection.from], index[&connection.to]);
+            incoming[to].push((from, connection.weight));
+            outgoing[from].push(to);
+            pending[to] += 1;
+        }
+
+        let mut ready: Vec<_> = (0..nodes.len()).filter(|&i| pending[i] == 0).collect();
+        let mut neurons = Vec::with_capacity(nodes.len());
+        while let Some(node) = ready.pop() {
+            for &to in &outgoing[node] {
+                pending[to] -= 1;
+                if pending[to] == 0 {
+                    ready.push(to);
+                }
+            }
+            if nodes[node].kind != NodeKind::Input {
+                neurons.push(Neuron {
+                    index: node,
+                    bias: nodes[node].bias,
+                    activation: nodes[node].activation,
+                    incoming: std::mem::take(&mut incoming[node]),
+                });
+            }
+        }
+        assert!(
+            pending.iter().all(|&count| count == 0),
+            "genome connections contain a cycle"
+        );
+
+        let of_kind = |kind| {
+            (0..nodes.len())
+                .filter(|&i| nodes[i].kind == kind)
+                .collect()
+        };
+
+        Self {
+            inputs: of_kind(NodeKind::Input),
+            outputs: of_kind(NodeKind::Output),
+            neurons,
+            values: vec![0.0; nodes.len()],
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.outputs.len()
+    }
+
+    pub fn propagate(&mut self, inputs: &[f32]) -> Vec<f32> {
+        self.evaluate(inputs);
+        self.values_of(&self.outputs)
+    }
+
+    /// Same as [`NeatNetwork::propagate`] but also returns the hidden node
+    /// values: `[inputs, hidden nodes in evaluation order, outputs]`.
+    pub fn propagate_traced(&mut self, inputs: &[f32]) -> Vec<Vec<f32>> {
+        self.evaluate(inputs);
+        let hidden: Vec<_> = self
+            .neurons
+            .iter()
+            .map(|neuron| neuron.index)
+            .filter(|index| !self.outputs.contains(index))
+            .collect();
+        vec![
+            inputs.to_vec(),
+            self.values_of(&hidden),
+            self.values_of(&self.outputs),
+        ]
+    }
+
+    fn values_of(&self, indices: &[usize]) -> Vec<f32> {
+        indices.iter().map(|&index| self.values[index]).collect()
+    }
+
+    fn evaluate(&mut self, inputs: &[f32]) {
+        assert_eq!(inputs.len(), self.inputs.len());
+
+        for (&index, &input) in self.inputs.iter().zip(inputs) {
+            self.values[index] = input;
+        }
+        for neuron in &self.neurons {
+            let sum = neuron
+                .incoming
+                .iter()
+                .map(|&(from, weight)| self.values[from] * weight)
+                .sum::<f32>();
+            self.values[neuron.index] = neuron.activation.apply(sum + neuron.bias);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::neat::{Genome, Innovations, NeatConfig};
+    use crate::Activation;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_propagate_minimal() {
+        let mut config = NeatConfig::new(2, 1);
+        config.output_activation = Activation::Identity;
+        let mut innovations = Innovations::new(3);
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let genome = Genome::minimal(&config, &mut innovations, &mut rng);
+
+        let weights: Vec<_> = genome.connections().iter().map(|c| c.weight).collect();
+        let bias = genome.nodes()[2].bias;
+        let mut network = genome.network();
+
+        assert_eq!(
+            network.propagate(&[2.0, -1.0]),
+            vec![2.0 * weights[0] - weights[1] + bias]
+        );
+    }
+
+    #[test]
+    fn test_propagate_through_hidden_node() {
+        let mut config = NeatConfig::new(1, 1);
+        config.output_activation = Activation::Identity;
+        config.hidden_activation = Activation::Identity;
+        let mut innovations = Innovations::new(2);
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let genome = Genome::minimal(&config, &mut innovations, &mut rng);
+        let before = genome.network().propagate(&[0.7]);
+
+        let mut split = genome;
+        split.mutate_add_node(&config, &mut innovations, &mut rng);
+
+        // identity hidden node with zero bias and unit input weight
+        assert_eq!(split.network().propagate(&[0.7]), before);
+
+        let trace = split.network().propagate_traced(&[0.7]);
+        assert_eq!(trace, vec![vec![0.7], vec![0.7], before]);
+    }
+}
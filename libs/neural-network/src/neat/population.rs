@@ -0,0 +1,320 @@
+use log::debug;
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use super::{Genome, Innovations};
+use crate::Activation;
+
+/// Rates and coefficients steering a NEAT run.
+#[derive(Clone, Debug)]
+pub struct NeatConfig {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub hidden_activation: Activation,
+    pub output_activation: Activation,
+    /// Chance a child has all its weights and biases perturbed.
+    pub weight_mutation_rate: f32,
+    /// Chance a single perturbed weight is redrawn instead of nudged.
+    pub weight_replace_rate: f32,
+    /// Largest nudge applied to a weight.
+    pub weight_power: f32,
+    pub add_connection_rate: f32,
+    pub add_node_rate: f32,
+    pub toggle_rate: f32,
+    /// Chance a child is bred from two parents rather than cloned from one.
+    pub crossover_rate: f32,
+    pub excess_coefficient: f32,
+    pub disjoint_coefficient: f32,
+    pub weight_coefficient: f32,
+    /// Genomes closer than this to a species representative join it.
+    pub compatibility_threshold: f32,
+    /// Fraction of each species, best first, allowed to reproduce.
+    pub survival_rate: f32,
+    /// Generations without improvement after which a species is dropped.
+    pub stagnation_limit: usize,
+}
+
+impl NeatConfig {
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        assert!(inputs > 0);
+        assert!(outputs > 0);
+        Self {
+            inputs,
+            outputs,
+            hidden_activation: Activation::ReLU,
+            output_activation: Activation::Tanh,
+            weight_mutation_rate: 0.8,
+            weight_replace_rate: 0.1,
+            weight_power: 0.5,
+            add_connection_rate: 0.05,
+            add_node_rate: 0.03,
+            toggle_rate: 0.01,
+            crossover_rate: 0.75,
+            excess_coefficient: 1.0,
+            disjoint_coefficient: 1.0,
+            weight_coefficient: 0.4,
+            compatibility_threshold: 3.0,
+            survival_rate: 0.2,
+            stagnation_limit: 15,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Species {
+    representative: Genome,
+    members: Vec<usize>,
+    best_fitness: f32,
+    staleness: usize,
+}
+
+impl Species {
+    /// Indices into [`Population::genomes`] of the genomes in this species.
+    pub fn members(&self) -> &[usize] {
+        &self.members
+    }
+
+    pub fn best_fitness(&self) -> f32 {
+        self.best_fitness
+    }
+}
+
+/// A generation of genomes together with the speciation and innovation
+/// history needed to breed the next one.
+#[derive(Clone, Debug)]
+pub struct Population {
+    config: NeatConfig,
+    innovations: Innovations,
+    genomes: Vec<Genome>,
+    species: Vec<Species>,
+}
+
+impl Population {
+    pub fn new(config: NeatConfig, size: usize, rng: &mut dyn RngCore) -> Self {
+        assert!(size > 0);
+        let mut innovations = Innovations::new(config.inputs + config.outputs);
+        let genomes = (0..size)
+            .map(|_| Genome::minimal(&config, &mut innovations, rng))
+            .collect();
+
+        Self {
+            config,
+            innovations,
+            genomes,
+            species: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &NeatConfig {
+        &self.config
+    }
+
+    pub fn genomes(&self) -> &[Genome] {
+        &self.genomes
+    }
+
+    pub fn species(&self) -> &[Species] {
+        &self.species
+    }
+
+    /// Replaces the genomes with their offspring. `fitnesses[i]` scores
+    /// `genomes()[i]`; any finite values work, they are shifted so the
+    /// weakest genome scores zero.
+    pub fn evolve(&mut self, rng: &mut dyn RngCore, fitnesses: &[f32]) {
+        assert_eq!(fitnesses.len(), self.genomes.len());
+
+        self.speciate();
+        self.cull_stagnant(fitnesses);
+
+        let offspring = self.offspring_counts(fitnesses);
+        let mut next = Vec::with_capacity(self.genomes.len());
+
+        for (species, &count) in self.species.iter_mut().zip(&offspring) {
+            let mut ranked = species.members.clone();
+            ranked.sort_by(|&a, &b| fitnesses[b].total_cmp(&fitnesses[a]));
+            species.representative = self.genomes[ranked[0]].clone();
+
+            if count == 0 {
+                continue;
+            }
+            next.push(self.genomes[ranked[0]].clone());
+
+            let survivors =
+                ((ranked.len() as f32 * self.config.survival_rate).ceil() as usize).max(1);
+            let parents = &ranked[..survivors];
+
+            for _ in 1..count {
+                let &a = parents.choose(rng).unwrap();
+                let mut child =
+                    if parents.len() > 1 && rng.gen_bool(self.config.crossover_rate as _) {
+                        let &b = parents.choose(rng).unwrap();
+                        let (fitter, other) = if fitnesses[a] >= fitnesses[b] {
+                            (a, b)
+                        } else {
+                            (b, a)
+                        };
+                        Genome::crossover(&self.genomes[fitter], &self.genomes[other], rng)
+                    } else {
+                        self.genomes[a].clone()
+                    };
+                child.mutate(&self.config, &mut self.innovations, rng);
+                next.push(child);
+            }
+        }
+
+        debug!(
+            "bred {} genomes across {} species",
+            next.len(),
+            self.species.len()
+        );
+        self.genomes = next;
+    }
+
+    fn speciate(&mut self) {
+        for species in &mut self.species {
+            species.members.clear();
+        }
+        for (index, genome) in self.genomes.iter().enumerate() {
+            let config = &self.config;
+            match self.species.iter_mut().find(|species| {
+                genome.distance(&species.representative, config) < config.compatibility_threshold
+            }) {
+                Some(species) => species.members.push(index),
+                None => self.species.push(Species {
+                    representative: genome.clone(),
+                    members: vec![index],
+                    best_fitness: f32::NEG_INFINITY,
+                    staleness: 0,
+                }),
+            }
+        }
+        self.species.retain(|species| !species.members.is_empty());
+    }
+
+    fn cull_stagnant(&mut self, fitnesses: &[f32]) {
+        for species in &mut self.species {
+            let best = species
+                .members
+                .iter()
+                .map(|&index| fitnesses[index])
+                .fold(f32::NEG_INFINITY, f32::max);
+            if best > species.best_fitness {
+                species.best_fitness = best;
+                species.staleness = 0;
+            } else {
+                species.staleness += 1;
+            }
+        }
+
+        let champion = self
+            .species
+            .iter()
+            .map(|species| species.best_fitness)
+            .fold(f32::NEG_INFINITY, f32::max);
+        let limit = self.config.stagnation_limit;
+        // the species holding the best genome always survives
+        self.species
+            .retain(|species| species.staleness <= limit || species.best_fitness >= champion);
+    }
+
+    /// Splits the population size across species in proportion to their mean
+    /// shifted fitness (explicit fitness sharing).
+    fn offspring_counts(&self, fitnesses: &[f32]) -> Vec<usize> {
+        let size = self.genomes.len();
+        let floor = fitnesses.iter().cloned().fold(f32::INFINITY, f32::min);
+        let scores: Vec<f32> = self
+            .species
+            .iter()
+            .map(|species| {
+                species
+                    .members
+                    .iter()
+                    .map(|&index| fitnesses[index] - floor)
+                    .sum::<f32>()
+                    / species.members.len() as f32
+            })
+            .collect();
+        let total: f32 = scores.iter().sum();
+
+        let shares: Vec<f32> = if total > 0.0 {
+            scores
+                .iter()
+                .map(|score| score / total * size as f32)
+                .collect()
+        } else {
+            let even = size as f32 / self.species.len() as f32;
+            vec![even; self.species.len()]
+        };
+
+        let mut counts: Vec<usize> = shares.iter().map(|share| share.floor() as usize).collect();
+        let mut remainders: Vec<usize> = (0..shares.len()).collect();
+        remainders.sort_by(|&a, &b| {
+            (shares[b] - shares[b].floor()).total_cmp(&(shares[a] - shares[a].floor()))
+        });
+        let missing = size - counts.iter().sum::<usize>();
+        for &index in remainders.iter().cycle().take(missing) {
+            counts[index] += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NeatConfig, Population};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_evolve_keeps_size() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut population = Population::new(NeatConfig::new(3, 2), 30, &mut rng);
+
+        for generation in 0..20 {
+            let fitnesses: Vec<_> = (0..30).map(|i| ((i * generation) % 7) as f32).collect();
+            population.evolve(&mut rng, &fitnesses);
+
+            assert_eq!(population.genomes().len(), 30);
+            assert!(!population.species().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_evolve_with_zero_fitness() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut population = Population::new(NeatConfig::new(2, 1), 10, &mut rng);
+
+        population.evolve(&mut rng, &[0.0; 10]);
+
+        assert_eq!(population.genomes().len(), 10);
+    }
+
+    #[test]
+    fn test_champion_survives() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut population = Population::new(NeatConfig::new(2, 1), 10, &mut rng);
+        let mut fitnesses = [0.0; 10];
+        fitnesses[3] = 5.0;
+        let champion = population.genomes()[3].clone();
+
+        population.evolve(&mut rng, &fitnesses);
+
+        assert!(population.genomes().contains(&champion));
+    }
+
+    #[test]
+    fn test_topology_grows() {
+        let mut config = NeatConfig::new(2, 1);
+        config.add_node_rate = 0.5;
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut population = Population::new(config, 10, &mut rng);
+
+        for _ in 0..10 {
+            population.evolve(&mut rng, &[1.0; 10]);
+        }
+
+        assert!(population
+            .genomes()
+            .iter()
+            .any(|genome| genome.nodes().len() > 3));
+    }
+}
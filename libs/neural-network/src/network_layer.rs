@@ -0,0 +1,203 @@
+use nalgebra::{DMatrix, DVector, DVectorSlice, DVectorSliceMut};
+use rand::RngCore;
+
+use crate::{
+    conv::{Conv1d, Shape},
+    hebbian::Hebbian,
+    layer::Layer,
+    recurrent::{Elman, Gru, GruScratch},
+    Float, LayerKind, LayerTopology, NetworkError,
+};
+
+/// Any of the layer implementations a [`crate::Network`] can chain together.
+#[derive(Debug, PartialEq)]
+pub(crate) enum NetworkLayer<T: Float> {
+    FeedForward(Layer<T>),
+    Elman(Elman<T>),
+    Gru(Box<Gru<T>>),
+    Conv1d(Conv1d<T>),
+    Hebbian(Hebbian<T>),
+}
+
+impl<T: Float> NetworkLayer<T> {
+    pub(crate) fn random(
+        input: &LayerTopology,
+        output: &LayerTopology,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        let (inputs, outputs, activation) = (input.neurons, output.neurons, output.activation);
+        let initializer = output.initializer;
+        match output.kind {
+            LayerKind::FeedForward => Self::FeedForward(Layer::random(
+                inputs,
+                outputs,
+                activation,
+                initializer,
+                output.mask.as_deref(),
+                rng,
+            )),
+            LayerKind::Elman => {
+                Self::Elman(Elman::random(inputs, outputs, activation, initializer, rng))
+            }
+            LayerKind::Gru => Self::Gru(Box::new(Gru::random(
+                inputs,
+                outputs,
+                activation,
+                initializer,
+                rng,
+            ))),
+            LayerKind::Hebbian => Self::Hebbian(Hebbian::random(
+                inputs,
+                outputs,
+                activation,
+                initializer,
+                rng,
+            )),
+            LayerKind::Conv1d { .. } => Self::Conv1d(Conv1d::random(
+                Shape::new(input, output),
+                activation,
+                initializer,
+                rng,
+            )),
+        }
+    }
+
+    pub(crate) fn try_from_weights(
+        input: &LayerTopology,
+        output: &LayerTopology,
+        weights: &mut dyn Iterator<Item = T>,
+    ) -> Result<Self, NetworkError> {
+        let (inputs, outputs, activation) = (input.neurons, output.neurons, output.activation);
+        Ok(match output.kind {
+            LayerKind::FeedForward => Self::FeedForward(Layer::try_from_weights(
+                inputs,
+                outputs,
+                activation,
+                output.mask.as_deref(),
+                weights,
+            )?),
+            LayerKind::Elman => Self::Elman(Elman::try_from_weights(
+                inputs, outputs, activation, weights,
+            )?),
+            LayerKind::Gru => Self::Gru(Box::new(Gru::try_from_weights(
+                inputs, outputs, activation, weights,
+            )?)),
+            LayerKind::Hebbian => Self::Hebbian(Hebbian::try_from_weights(
+                inputs, outputs, activation, weights,
+            )?),
+            LayerKind::Conv1d { .. } => Self::Conv1d(Conv1d::try_from_weights(
+                Shape::new(input, output),
+                activation,
+                weights,
+            )?),
+        })
+    }
+
+    /// `None` if the count overflows.
+    pub(crate) fn weight_count(input: &LayerTopology, output: &LayerTopology) -> Option<usize> {
+        match output.kind {
+            LayerKind::FeedForward => {
+                Layer::<T>::weight_count(input.neurons, output.neurons, output.mask.as_deref())
+            }
+            LayerKind::Elman => Elman::<T>::weight_count(input.neurons, output.neurons),
+            LayerKind::Gru => Gru::<T>::weight_count(input.neurons, output.neurons),
+            LayerKind::Hebbian => Hebbian::<T>::weight_count(input.neurons, output.neurons),
+            LayerKind::Conv1d { .. } => Conv1d::<T>::weight_count(Shape::new(input, output)),
+        }
+    }
+
+    pub(crate) fn cast<U: Float>(&self) -> NetworkLayer<U> {
+        match self {
+            Self::FeedForward(layer) => NetworkLayer::FeedForward(layer.cast()),
+            Self::Elman(layer) => NetworkLayer::Elman(layer.cast()),
+            Self::Gru(layer) => NetworkLayer::Gru(Box::new(layer.cast())),
+            Self::Conv1d(layer) => NetworkLayer::Conv1d(layer.cast()),
+            Self::Hebbian(layer) => NetworkLayer::Hebbian(layer.cast()),
+        }
+    }
+
+    pub(crate) fn weights(&self) -> Box<dyn Iterator<Item = T> + '_> {
+        match self {
+            Self::FeedForward(layer) => Box::new(layer.weights()),
+            Self::Elman(layer) => Box::new(layer.weights()),
+            Self::Gru(layer) => Box::new(layer.weights()),
+            Self::Conv1d(layer) => Box::new(layer.weights()),
+            Self::Hebbian(layer) => Box::new(layer.weights()),
+        }
+    }
+
+    pub(crate) fn reset_state(&mut self) {
+        match self {
+            Self::FeedForward(_) | Self::Conv1d(_) => {}
+            Self::Elman(layer) => layer.reset_state(),
+            Self::Gru(layer) => layer.reset_state(),
+            Self::Hebbian(layer) => layer.reset_state(),
+        }
+    }
+
+    pub(crate) fn propagate(&mut self, inputs: &DVector<T>, output_neurons: usize) -> DVector<T> {
+        match self {
+            Self::FeedForward(layer) => layer.propagate(inputs),
+            _ => {
+                let mut outputs = DVector::zeros(output_neurons);
+                self.propagate_into(
+                    &inputs.rows(0, inputs.len()),
+                    &mut outputs.rows_mut(0, output_neurons),
+                );
+                outputs
+            }
+        }
+    }
+
+    pub(crate) fn propagate_into(
+        &mut self,
+        inputs: &DVectorSlice<T>,
+        outputs: &mut DVectorSliceMut<T>,
+    ) {
+        match self {
+            Self::FeedForward(layer) => layer.propagate_into(inputs, outputs),
+            Self::Elman(layer) => layer.propagate_into(inputs, outputs),
+            Self::Gru(layer) => layer.propagate_into(inputs, outputs),
+            Self::Conv1d(layer) => layer.propagate_into(inputs, outputs),
+            Self::Hebbian(layer) => layer.propagate_into(inputs, outputs),
+        }
+    }
+
+    /// Recurrent and Hebbian layers read their current state for every column
+    /// but do not advance it.
+    pub(crate) fn propagate_batch(&self, inputs: &DMatrix<T>, output_neurons: usize) -> DMatrix<T> {
+        match self {
+            Self::FeedForward(layer) => layer.propagate_batch(inputs),
+            Self::Elman(layer) => {
+                Self::step_columns(inputs, output_neurons, |i, o| layer.step_into(i, o))
+            }
+            Self::Gru(layer) => {
+                let mut scratch = GruScratch::new(layer.neurons());
+                Self::step_columns(inputs, output_neurons, |i, o| {
+                    layer.step_into(i, o, &mut scratch)
+                })
+            }
+            Self::Conv1d(layer) => {
+                Self::step_columns(inputs, output_neurons, |i, o| layer.propagate_into(i, o))
+            }
+            Self::Hebbian(layer) => {
+                Self::step_columns(inputs, output_neurons, |i, o| layer.step_into(i, o))
+            }
+        }
+    }
+
+    fn step_columns(
+        inputs: &DMatrix<T>,
+        output_neurons: usize,
+        mut step: impl FnMut(&DVectorSlice<T>, &mut DVectorSliceMut<T>),
+    ) -> DMatrix<T> {
+        let mut outputs = DMatrix::zeros(output_neurons, inputs.ncols());
+        for (input, mut output) in inputs.column_iter().zip(outputs.column_iter_mut()) {
+            step(
+                &input.rows(0, input.len()),
+                &mut output.rows_mut(0, output_neurons),
+            );
+        }
+        outputs
+    }
+}
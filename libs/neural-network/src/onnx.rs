@@ -0,0 +1,359 @@
+//! Export of feed-forward networks to the [ONNX](https://onnx.ai) format.
+//!
+//! Every layer becomes a `Gemm` node (`y = x * Wᵀ + b`) followed by its
+//! activation. The graph input is `input` with shape `[batch, inputs]` and
+//! the graph output is `output` with shape `[batch, outputs]`.
+
+use std::io::Write;
+
+use prost::Message;
+
+use crate::{Activation, Network, NetworkError, SerializationError};
+
+/// ONNX IR version written to the model, matching ONNX 1.8.
+pub const IR_VERSION: i64 = 7;
+
+/// Default-domain operator set the exported graph is expressed in.
+pub const OPSET_VERSION: i64 = 13;
+
+const TENSOR_FLOAT: i32 = 1;
+const ATTRIBUTE_FLOAT: i32 = 1;
+const ATTRIBUTE_INT: i32 = 2;
+
+// Hand-written subset of `onnx.proto` covering what the exporter emits.
+// Field numbers must match the upstream schema. Fields the exporter always
+// sets are plain, but attribute values are `optional` as upstream: a proto3
+// scalar holding zero is not written at all.
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct ModelProto {
+    #[prost(int64, tag = "1")]
+    pub ir_version: i64,
+    #[prost(message, repeated, tag = "8")]
+    pub opset_import: Vec<OperatorSetIdProto>,
+    #[prost(string, tag = "2")]
+    pub producer_name: String,
+    #[prost(string, tag = "3")]
+    pub producer_version: String,
+    #[prost(message, optional, tag = "7")]
+    pub graph: Option<GraphProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct OperatorSetIdProto {
+    #[prost(string, tag = "1")]
+    pub domain: String,
+    #[prost(int64, tag = "2")]
+    pub version: i64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct GraphProto {
+    #[prost(message, repeated, tag = "1")]
+    pub node: Vec<NodeProto>,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(message, repeated, tag = "5")]
+    pub initializer: Vec<TensorProto>,
+    #[prost(message, repeated, tag = "11")]
+    pub input: Vec<ValueInfoProto>,
+    #[prost(message, repeated, tag = "12")]
+    pub output: Vec<ValueInfoProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct NodeProto {
+    #[prost(string, repeated, tag = "1")]
+    pub input: Vec<String>,
+    #[prost(string, repeated, tag = "2")]
+    pub output: Vec<String>,
+    #[prost(string, tag = "3")]
+    pub name: String,
+    #[prost(string, tag = "4")]
+    pub op_type: String,
+    #[prost(message, repeated, tag = "5")]
+    pub attribute: Vec<AttributeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct AttributeProto {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(int32, tag = "20")]
+    pub r#type: i32,
+    #[prost(float, optional, tag = "2")]
+    pub f: Option<f32>,
+    #[prost(int64, optional, tag = "3")]
+    pub i: Option<i64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct TensorProto {
+    // upstream is proto2, where only `float_data` is declared packed
+    #[prost(int64, repeated, packed = "false", tag = "1")]
+    pub dims: Vec<i64>,
+    #[prost(int32, tag = "2")]
+    pub data_type: i32,
+    #[prost(float, repeated, tag = "4")]
+    pub float_data: Vec<f32>,
+    #[prost(string, tag = "8")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct ValueInfoProto {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(message, optional, tag = "2")]
+    pub r#type: Option<TypeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct TypeProto {
+    #[prost(message, optional, tag = "1")]
+    pub tensor_type: Option<TensorTypeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct TensorTypeProto {
+    #[prost(int32, tag = "1")]
+    pub elem_type: i32,
+    #[prost(message, optional, tag = "2")]
+    pub shape: Option<TensorShapeProto>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct TensorShapeProto {
+    #[prost(message, repeated, tag = "1")]
+    pub dim: Vec<Dimension>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct Dimension {
+    #[prost(int64, optional, tag = "1")]
+    pub dim_value: Option<i64>,
+    #[prost(string, optional, tag = "2")]
+    pub dim_param: Option<String>,
+}
+
+impl Network {
+    /// Encodes the network as an ONNX model. Only feed-forward layers without
+    /// skip connections can be exported; pruned connections are written as
+    /// zero weights.
+    pub fn to_onnx(&self) -> Result<Vec<u8>, SerializationError> {
+        Ok(self.onnx_model()?.encode_to_vec())
+    }
+
+    pub fn save_onnx(&self, mut writer: impl Write) -> Result<(), SerializationError> {
+        writer.write_all(&self.to_onnx()?)?;
+        Ok(())
+    }
+
+    fn onnx_model(&self) -> Result<ModelProto, NetworkError> {
+        let mut node = Vec::new();
+        let mut initializer = Vec::new();
+        let mut current = "input".to_owned();
+
+        let layers = self.feed_forward_chain()?;
+        for (index, layer) in layers.iter().enumerate() {
+            let weights = layer.weight_matrix();
+            let (weight_name, bias_name) = (format!("W{}", index), format!("B{}", index));
+            let gemm = format!("gemm{}", index);
+            let output = if index + 1 == layers.len() {
+                "output".to_owned()
+            } else {
+                format!("hidden{}", index)
+            };
+
+            initializer.push(TensorProto {
+                dims: vec![weights.nrows() as i64, weights.ncols() as i64],
+                data_type: TENSOR_FLOAT,
+                // row-major, one row per neuron
+                float_data: weights.transpose().as_slice().to_vec(),
+                name: weight_name.clone(),
+            });
+            initializer.push(TensorProto {
+                dims: vec![weights.nrows() as i64],
+                data_type: TENSOR_FLOAT,
+                float_data: layer.biases().as_slice().to_vec(),
+                name: bias_name.clone(),
+            });
+            node.push(NodeProto {
+                input: vec![current, weight_name, bias_name],
+                output: vec![gemm.clone()],
+                name: gemm.clone(),
+                op_type: "Gemm".to_owned(),
+                attribute: vec![AttributeProto {
+                    name: "transB".to_owned(),
+                    r#type: ATTRIBUTE_INT,
+                    i: Some(1),
+                    ..Default::default()
+                }],
+            });
+            node.push(activation_node(
+                layer.activation(),
+                gemm,
+                output.clone(),
+                index,
+            ));
+            current = output;
+        }
+
+        let first = self.topology[0].neurons;
+        let last = self.topology[self.topology.len() - 1].neurons;
+        Ok(ModelProto {
+            ir_version: IR_VERSION,
+            opset_import: vec![OperatorSetIdProto {
+                domain: String::new(),
+                version: OPSET_VERSION,
+            }],
+            producer_name: env!("CARGO_PKG_NAME").to_owned(),
+            producer_version: env!("CARGO_PKG_VERSION").to_owned(),
+            graph: Some(GraphProto {
+                node,
+                name: "network".to_owned(),
+                initializer,
+                input: vec![value_info("input", first)],
+                output: vec![value_info("output", last)],
+            }),
+        })
+    }
+}
+
+fn activation_node(
+    activation: Activation,
+    input: String,
+    output: String,
+    index: usize,
+) -> NodeProto {
+    let (op_type, attribute) = match activation {
+        Activation::Identity => ("Identity", vec![]),
+        Activation::ReLU => ("Relu", vec![]),
+        Activation::LeakyReLU(slope) => (
+            "LeakyRelu",
+            vec![AttributeProto {
+                name: "alpha".to_owned(),
+                r#type: ATTRIBUTE_FLOAT,
+                f: Some(slope),
+                ..Default::default()
+            }],
+        ),
+        Activation::Tanh => ("Tanh", vec![]),
+        Activation::Sigmoid => ("Sigmoid", vec![]),
+        Activation::Softsign => ("Softsign", vec![]),
+    };
+    NodeProto {
+        input: vec![input],
+        output: vec![output],
+        name: format!("activation{}", index),
+        op_type: op_type.to_owned(),
+        attribute,
+    }
+}
+
+/// A float tensor of shape `[batch, width]` with a symbolic batch size.
+fn value_info(name: &str, width: usize) -> ValueInfoProto {
+    ValueInfoProto {
+        name: name.to_owned(),
+        r#type: Some(TypeProto {
+            tensor_type: Some(TensorTypeProto {
+                elem_type: TENSOR_FLOAT,
+                shape: Some(TensorShapeProto {
+                    dim: vec![
+                        Dimension {
+                            dim_value: None,
+                            dim_param: Some("batch".to_owned()),
+                        },
+                        Dimension {
+                            dim_value: Some(width as i64),
+                            dim_param: None,
+                        },
+                    ],
+                }),
+            }),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ModelProto, OPSET_VERSION};
+    use crate::{Activation, LayerKind, LayerTopology, Network, NetworkError, SerializationError};
+    use prost::Message;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn topology() -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::LeakyReLU(0.1),
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_export_matches_weights() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network: Network = Network::random(&mut rng, &topology());
+
+        let bytes = network.to_onnx().unwrap();
+        let model = ModelProto::decode(bytes.as_slice()).unwrap();
+
+        assert_eq!(model.opset_import[0].version, OPSET_VERSION);
+        let graph = model.graph.unwrap();
+        let ops: Vec<_> = graph.node.iter().map(|n| n.op_type.as_str()).collect();
+        assert_eq!(ops, ["Gemm", "LeakyRelu", "Gemm", "Tanh"]);
+        assert_eq!(graph.node[1].attribute[0].f, Some(0.1));
+        assert_eq!(graph.node[0].input, ["input", "W0", "B0"]);
+        assert_eq!(graph.node[2].input, ["hidden0", "W1", "B1"]);
+        assert_eq!(graph.node[3].output, ["output"]);
+        assert_eq!(graph.input[0].name, "input");
+        assert_eq!(graph.output[0].name, "output");
+
+        let names: Vec<_> = graph.initializer.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["W0", "B0", "W1", "B1"]);
+        assert_eq!(graph.initializer[0].dims, [4, 3]);
+        assert_eq!(graph.initializer[2].dims, [2, 4]);
+
+        // rebuild the `bias, weights..` per neuron layout of Network::weights
+        let mut weights = Vec::new();
+        for pair in graph.initializer.chunks(2) {
+            let (matrix, biases) = (&pair[0], &pair[1]);
+            let width = matrix.dims[1] as usize;
+            for (row, bias) in matrix.float_data.chunks(width).zip(&biases.float_data) {
+                weights.push(*bias);
+                weights.extend_from_slice(row);
+            }
+        }
+        assert_eq!(weights, network.weights().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_recurrent_layer_unsupported() {
+        let mut layers = topology();
+        layers[1].kind = LayerKind::Gru;
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network: Network = Network::random(&mut rng, &layers);
+
+        assert!(matches!(
+            network.to_onnx(),
+            Err(SerializationError::Network(
+                NetworkError::UnsupportedLayer {
+                    layer: 1,
+                    kind: LayerKind::Gru
+                }
+            ))
+        ));
+    }
+}
@@ -0,0 +1,350 @@
+//! Int8 inference for feed-forward networks.
+//!
+//! Weights are quantized symmetrically per layer. Activations entering each
+//! layer use an affine per-layer scale and zero point calibrated on sample
+//! inputs. Every layer runs an `i8 x i8 -> i32` matrix-vector product, then
+//! dequantizes the sums to apply its activation in `f32`.
+
+use std::fmt::Display;
+
+use nalgebra::DMatrix;
+
+use crate::{Activation, Network, NetworkError};
+
+/// Affine mapping between `f32` and `i8`: `real = scale * (q - zero_point)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantization {
+    pub scale: f32,
+    pub zero_point: i32,
+}
+
+impl Quantization {
+    /// Covers `[min, max]`, widened to contain zero so that zero stays exact.
+    pub fn from_range(min: f32, max: f32) -> Self {
+        let (min, max) = (min.min(0.0), max.max(0.0));
+        let scale = if max > min { (max - min) / 255.0 } else { 1.0 };
+        Self {
+            scale,
+            zero_point: (-128.0 - min / scale).round() as i32,
+        }
+    }
+
+    /// Saturates at the ends of the `i8` range, however far out `x` is.
+    pub fn quantize(&self, x: f32) -> i8 {
+        ((x / self.scale).round() + self.zero_point as f32).clamp(-128.0, 127.0) as i8
+    }
+
+    pub fn dequantize(&self, q: i8) -> f32 {
+        self.scale * (q as i32 - self.zero_point) as f32
+    }
+}
+
+#[derive(Clone, Debug)]
+struct QuantizedLayer {
+    input: Quantization,
+    weight_scale: f32,
+    /// Row-major, one row per neuron.
+    weights: Vec<i8>,
+    /// In units of `input.scale * weight_scale`.
+    biases: Vec<i32>,
+    /// Sum of every weight row, used to cancel the input zero point.
+    row_sums: Vec<i32>,
+    activation: Activation,
+}
+
+impl QuantizedLayer {
+    fn propagate(&self, inputs: &[f32], buffer: &mut Vec<i32>) -> Vec<f32> {
+        assert_eq!(inputs.len(), self.weights.len() / self.biases.len());
+        buffer.clear();
+        buffer.extend(inputs.iter().map(|&x| self.input.quantize(x) as i32));
+
+        let scale = self.input.scale * self.weight_scale;
+        self.weights
+            .chunks(inputs.len())
+            .zip(&self.biases)
+            .zip(&self.row_sums)
+            .map(|((row, bias), row_sum)| {
+                let dot: i32 = row
+                    .iter()
+                    .zip(buffer.iter())
+                    .map(|(&w, &x)| w as i32 * x)
+                    .sum();
+                let sum = bias + dot - self.input.zero_point * row_sum;
+                self.activation.apply(sum as f32 * scale)
+            })
+            .collect()
+    }
+}
+
+/// Int8 copy of a feed-forward [`Network`] without skip connections.
+#[derive(Clone, Debug)]
+pub struct QuantizedNetwork {
+    layers: Vec<QuantizedLayer>,
+}
+
+impl QuantizedNetwork {
+    /// Quantizes `network`, picking each layer's input range from the
+    /// activations `calibration` produces. Use inputs representative of what
+    /// the network will see; values outside the calibrated range saturate.
+    pub fn new(network: &Network, calibration: &[Vec<f32>]) -> Result<Self, NetworkError> {
+        if calibration.is_empty() {
+            return Err(NetworkError::EmptyCalibration);
+        }
+
+        let mut activations = samples(network, calibration)?;
+        let mut layers = Vec::with_capacity(network.layers.len());
+
+        for layer in network.feed_forward_chain()? {
+            let input = Quantization::from_range(activations.min(), activations.max());
+            let matrix = layer.weight_matrix();
+            let largest = matrix.amax();
+            let weight_scale = if largest > 0.0 { largest / 127.0 } else { 1.0 };
+
+            let weights: Vec<i8> = matrix
+                .transpose()
+                .iter()
+                .map(|w| (w / weight_scale).round() as i8)
+                .collect();
+            let row_sums = weights
+                .chunks(matrix.ncols())
+                .map(|row| row.iter().map(|&w| w as i32).sum())
+                .collect();
+            let biases = layer
+                .biases()
+                .iter()
+                .map(|b| (b / (input.scale * weight_scale)).round() as i32)
+                .collect();
+
+            layers.push(QuantizedLayer {
+                input,
+                weight_scale,
+                weights,
+                biases,
+                row_sums,
+                activation: layer.activation(),
+            });
+            activations = layer.propagate_batch(&activations);
+        }
+
+        Ok(Self { layers })
+    }
+
+    /// Scale and zero point of the values entering each layer.
+    pub fn input_quantizations(&self) -> impl Iterator<Item = Quantization> + '_ {
+        self.layers.iter().map(|layer| layer.input)
+    }
+
+    pub fn propagate(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut buffer = Vec::with_capacity(inputs.len());
+        self.layers.iter().fold(inputs.to_vec(), |values, layer| {
+            layer.propagate(&values, &mut buffer)
+        })
+    }
+
+    /// Runs `inputs` through both this network and `network`, the `f32`
+    /// network it was built from, and measures how far the outputs drift.
+    pub fn compare(
+        &self,
+        network: &Network,
+        inputs: &[Vec<f32>],
+    ) -> Result<AccuracyReport, NetworkError> {
+        let expected = network.propagate_batch(&samples(network, inputs)?);
+        let mut report = AccuracyReport {
+            samples: inputs.len(),
+            mean_error: 0.0,
+            max_error: 0.0,
+        };
+
+        let mut count = 0;
+        for (input, expected) in inputs.iter().zip(expected.column_iter()) {
+            for (actual, expected) in self.propagate(input).iter().zip(expected.iter()) {
+                let error = (actual - expected).abs();
+                report.mean_error += error;
+                report.max_error = report.max_error.max(error);
+                count += 1;
+            }
+        }
+        if count > 0 {
+            report.mean_error /= count as f32;
+        }
+        Ok(report)
+    }
+}
+
+/// Output drift of a [`QuantizedNetwork`] against its `f32` original.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccuracyReport {
+    pub samples: usize,
+    /// Mean absolute difference over every output of every sample.
+    pub mean_error: f32,
+    pub max_error: f32,
+}
+
+impl Display for AccuracyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} samples: mean absolute error {:.5}, max absolute error {:.5}",
+            self.samples, self.mean_error, self.max_error
+        )
+    }
+}
+
+/// One column per input vector.
+fn samples(network: &Network, inputs: &[Vec<f32>]) -> Result<DMatrix<f32>, NetworkError> {
+    let width = network.topology[0].neurons;
+    if let Some(input) = inputs.iter().find(|input| input.len() != width) {
+        return Err(NetworkError::InputSize {
+            expected: width,
+            received: input.len(),
+        });
+    }
+    Ok(DMatrix::from_fn(width, inputs.len(), |row, col| {
+        inputs[col][row]
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Quantization, QuantizedNetwork};
+    use crate::{Activation, Initializer, LayerKind, LayerTopology, Network, NetworkError};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    /// Shaped like a bird brain: nine eye cells, a ReLU layer, two signed outputs.
+    fn brain() -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 9,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 18,
+                activation: Activation::ReLU,
+                initializer: Initializer::He,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                initializer: Initializer::Xavier,
+                ..Default::default()
+            },
+        ]
+    }
+
+    /// Mostly empty cells with the odd food item, energies in `[0, 1]`.
+    fn eye_inputs(rng: &mut ChaCha8Rng, count: usize) -> Vec<Vec<f32>> {
+        (0..count)
+            .map(|_| {
+                (0..9)
+                    .map(|_| {
+                        if rng.gen_bool(0.3) {
+                            rng.gen_range(0.0..1.0)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_quantization_round_trip() {
+        let quantization = Quantization::from_range(0.0, 1.0);
+
+        assert_eq!(quantization.quantize(0.0), -128);
+        assert_eq!(quantization.quantize(1.0), 127);
+        assert_eq!(quantization.dequantize(quantization.quantize(0.0)), 0.0);
+        assert!((quantization.dequantize(quantization.quantize(0.5)) - 0.5).abs() < 0.002);
+        assert_eq!(quantization.quantize(7.0), 127);
+    }
+
+    #[test]
+    fn test_quantization_saturates() {
+        let quantization = Quantization::from_range(0.0, 1.0);
+
+        assert_eq!(quantization.quantize(-1e8), -128);
+        assert_eq!(quantization.quantize(1e8), 127);
+        assert_eq!(quantization.quantize(f32::NEG_INFINITY), -128);
+        assert_eq!(quantization.quantize(f32::INFINITY), 127);
+
+        let quantization = Quantization::from_range(-1.0, 0.0);
+        assert_eq!(quantization.quantize(-1e8), -128);
+        assert_eq!(quantization.quantize(1e8), 127);
+    }
+
+    #[test]
+    fn test_accuracy_on_eye_inputs() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network: Network = Network::random(&mut rng, &brain());
+        let calibration = eye_inputs(&mut rng, 200);
+        let quantized = QuantizedNetwork::new(&network, &calibration).unwrap();
+
+        let report = quantized
+            .compare(&network, &eye_inputs(&mut rng, 500))
+            .unwrap();
+
+        assert_eq!(report.samples, 500);
+        assert!(report.mean_error < 0.01, "{}", report);
+        assert!(report.max_error < 0.05, "{}", report);
+    }
+
+    #[test]
+    fn test_empty_calibration() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network: Network = Network::random(&mut rng, &brain());
+
+        assert_eq!(
+            QuantizedNetwork::new(&network, &[]).err(),
+            Some(NetworkError::EmptyCalibration)
+        );
+    }
+
+    #[test]
+    fn test_input_width_checked() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network: Network = Network::random(&mut rng, &brain());
+        let mut inputs = eye_inputs(&mut rng, 3);
+        inputs[1].pop();
+        let error = NetworkError::InputSize {
+            expected: 9,
+            received: 8,
+        };
+
+        assert_eq!(
+            QuantizedNetwork::new(&network, &inputs).err(),
+            Some(error.clone())
+        );
+
+        let quantized = QuantizedNetwork::new(&network, &eye_inputs(&mut rng, 3)).unwrap();
+        assert_eq!(quantized.compare(&network, &inputs).err(), Some(error));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_propagate_wrong_width() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network: Network = Network::random(&mut rng, &brain());
+        let quantized = QuantizedNetwork::new(&network, &eye_inputs(&mut rng, 3)).unwrap();
+
+        quantized.propagate(&[0.5; 8]);
+    }
+
+    #[test]
+    fn test_recurrent_layer_unsupported() {
+        let mut layers = brain();
+        layers[1].kind = LayerKind::Elman;
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let network: Network = Network::random(&mut rng, &layers);
+
+        assert_eq!(
+            QuantizedNetwork::new(&network, &eye_inputs(&mut rng, 1)).err(),
+            Some(NetworkError::UnsupportedLayer {
+                layer: 1,
+                kind: LayerKind::Elman
+            })
+        );
+    }
+}
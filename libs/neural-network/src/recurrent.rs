@@ -0,0 +1,441 @@
+use std::iter::once;
+
+use log::debug;
+use nalgebra::{DMatrix, DVector, DVectorSlice, DVectorSliceMut};
+use rand::RngCore;
+
+use crate::{Activation, Float, Initializer, NetworkError};
+
+/// Input weights, recurrent weights and biases feeding one set of neurons.
+///
+/// Weights are laid out per neuron as `bias, input weights.., recurrent
+/// weights..`, matching the feed-forward [`crate::layer::Layer`] layout with
+/// the recurrent row appended.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Gate<T: Float> {
+    weights: DMatrix<T>,
+    recurrent: DMatrix<T>,
+    biases: DVector<T>,
+}
+
+impl<T: Float> Gate<T> {
+    /// Each neuron sees the inputs and the previous state, so both count
+    /// towards its fan-in.
+    fn random(
+        input_neurons: usize,
+        output_neurons: usize,
+        initializer: Initializer,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        let fan_in = input_neurons + output_neurons;
+        Self {
+            weights: DMatrix::from_fn(output_neurons, input_neurons, |_, _| {
+                T::cast_f32(initializer.weight(fan_in, output_neurons, rng))
+            }),
+            recurrent: DMatrix::from_fn(output_neurons, output_neurons, |_, _| {
+                T::cast_f32(initializer.weight(fan_in, output_neurons, rng))
+            }),
+            biases: DVector::from_fn(output_neurons, |_, _| T::cast_f32(initializer.bias(rng))),
+        }
+    }
+
+    fn try_from_weights(
+        input_neurons: usize,
+        output_neurons: usize,
+        neuron_weights: &mut dyn Iterator<Item = T>,
+    ) -> Result<Self, NetworkError> {
+        let expected = Self::weight_count(input_neurons, output_neurons).unwrap_or(usize::MAX);
+        let mut received = 0;
+        let mut next = || {
+            let weight = neuron_weights
+                .next()
+                .ok_or(NetworkError::TooFewWeights { expected, received });
+            received += 1;
+            weight
+        };
+
+        let mut gate = Self {
+            weights: DMatrix::zeros(output_neurons, input_neurons),
+            recurrent: DMatrix::zeros(output_neurons, output_neurons),
+            biases: DVector::zeros(output_neurons),
+        };
+        for row in 0..output_neurons {
+            gate.biases[row] = next()?;
+            for col in 0..input_neurons {
+                gate.weights[(row, col)] = next()?;
+            }
+            for col in 0..output_neurons {
+                gate.recurrent[(row, col)] = next()?;
+            }
+        }
+        Ok(gate)
+    }
+
+    /// `None` if the count overflows.
+    fn weight_count(input_neurons: usize, output_neurons: usize) -> Option<usize> {
+        output_neurons.checked_mul(input_neurons.checked_add(output_neurons)?.checked_add(1)?)
+    }
+
+    fn weights(&self) -> impl Iterator<Item = T> + '_ {
+        self.weights
+            .row_iter()
+            .zip(self.recurrent.row_iter())
+            .zip(self.biases.iter())
+            .flat_map(|((row, recurrent), &bias)| {
+                once(bias)
+                    .chain(row.iter().cloned())
+                    .chain(recurrent.iter().cloned())
+                    .collect::<Vec<_>>()
+            })
+    }
+
+    fn cast<U: Float>(&self) -> Gate<U> {
+        let cast = |x: T| U::from_subset(&x.as_f64());
+        Gate {
+            weights: self.weights.map(cast),
+            recurrent: self.recurrent.map(cast),
+            biases: self.biases.map(cast),
+        }
+    }
+
+    /// `outputs = W * inputs + U * hidden + b`
+    fn preactivation_into(
+        &self,
+        inputs: &DVectorSlice<T>,
+        hidden: &DVector<T>,
+        outputs: &mut DVectorSliceMut<T>,
+    ) {
+        outputs.gemv(T::one(), &self.weights, inputs, T::zero());
+        outputs.gemv(T::one(), &self.recurrent, hidden, T::one());
+        *outputs += &self.biases;
+    }
+}
+
+/// Elman cell: `h' = f(W x + U h + b)`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Elman<T: Float> {
+    gate: Gate<T>,
+    activation: Activation,
+    state: DVector<T>,
+}
+
+impl<T: Float> Elman<T> {
+    pub(crate) fn random(
+        input_neurons: usize,
+        output_neurons: usize,
+        activation: Activation,
+        initializer: Initializer,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        debug!(
+            "create new random elman layer with {} inputs and {} neurons",
+            input_neurons, output_neurons
+        );
+        Self {
+            gate: Gate::random(input_neurons, output_neurons, initializer, rng),
+            activation,
+            state: DVector::zeros(output_neurons),
+        }
+    }
+
+    pub(crate) fn try_from_weights(
+        input_neurons: usize,
+        output_neurons: usize,
+        activation: Activation,
+        neuron_weights: &mut dyn Iterator<Item = T>,
+    ) -> Result<Self, NetworkError> {
+        Ok(Self {
+            gate: Gate::try_from_weights(input_neurons, output_neurons, neuron_weights)?,
+            activation,
+            state: DVector::zeros(output_neurons),
+        })
+    }
+
+    pub(crate) fn weight_count(input_neurons: usize, output_neurons: usize) -> Option<usize> {
+        Gate::<T>::weight_count(input_neurons, output_neurons)
+    }
+
+    /// The same layer, hidden state included, in another precision.
+    pub(crate) fn cast<U: Float>(&self) -> Elman<U> {
+        Elman {
+            gate: self.gate.cast(),
+            activation: self.activation,
+            state: self.state.map(|x| U::from_subset(&x.as_f64())),
+        }
+    }
+
+    pub(crate) fn weights(&self) -> impl Iterator<Item = T> + '_ {
+        self.gate.weights()
+    }
+
+    pub(crate) fn reset_state(&mut self) {
+        self.state.fill(T::zero());
+    }
+
+    /// Computes the next hidden state without storing it.
+    pub(crate) fn step_into(&self, inputs: &DVectorSlice<T>, outputs: &mut DVectorSliceMut<T>) {
+        self.gate.preactivation_into(inputs, &self.state, outputs);
+        outputs.apply(|x| *x = self.activation.apply(*x));
+    }
+
+    pub(crate) fn propagate_into(
+        &mut self,
+        inputs: &DVectorSlice<T>,
+        outputs: &mut DVectorSliceMut<T>,
+    ) {
+        self.step_into(inputs, outputs);
+        self.state.copy_from(outputs);
+    }
+}
+
+/// Gated recurrent unit:
+///
+/// ```text
+/// z  = sigmoid(Wz x + Uz h + bz)
+/// r  = sigmoid(Wr x + Ur h + br)
+/// n  = f(Wn x + Un (r * h) + bn)
+/// h' = (1 - z) * n + z * h
+/// ```
+///
+/// `f` is the activation from the layer topology, usually [`Activation::Tanh`].
+#[derive(Debug, PartialEq)]
+pub(crate) struct Gru<T: Float> {
+    update: Gate<T>,
+    reset: Gate<T>,
+    candidate: Gate<T>,
+    activation: Activation,
+    state: DVector<T>,
+    /// Gate outputs of the step being computed, kept so that
+    /// [`Gru::propagate_into`] never allocates.
+    scratch: GruScratch<T>,
+}
+
+/// Gate outputs of a [`Gru`] step.
+#[derive(Debug, PartialEq)]
+pub(crate) struct GruScratch<T: Float> {
+    update: DVector<T>,
+    reset: DVector<T>,
+}
+
+impl<T: Float> GruScratch<T> {
+    pub(crate) fn new(neurons: usize) -> Self {
+        Self {
+            update: DVector::zeros(neurons),
+            reset: DVector::zeros(neurons),
+        }
+    }
+}
+
+impl<T: Float> Default for GruScratch<T> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T: Float> Gru<T> {
+    pub(crate) fn random(
+        input_neurons: usize,
+        output_neurons: usize,
+        activation: Activation,
+        initializer: Initializer,
+        rng: &mut dyn RngCore,
+    ) -> Self {
+        debug!(
+            "create new random gru layer with {} inputs and {} neurons",
+            input_neurons, output_neurons
+        );
+        Self {
+            update: Gate::random(input_neurons, output_neurons, initializer, rng),
+            reset: Gate::random(input_neurons, output_neurons, initializer, rng),
+            candidate: Gate::random(input_neurons, output_neurons, initializer, rng),
+            activation,
+            state: DVector::zeros(output_neurons),
+            scratch: GruScratch::new(output_neurons),
+        }
+    }
+
+    pub(crate) fn try_from_weights(
+        input_neurons: usize,
+        output_neurons: usize,
+        activation: Activation,
+        neuron_weights: &mut dyn Iterator<Item = T>,
+    ) -> Result<Self, NetworkError> {
+        Ok(Self {
+            update: Gate::try_from_weights(input_neurons, output_neurons, neuron_weights)?,
+            reset: Gate::try_from_weights(input_neurons, output_neurons, neuron_weights)?,
+            candidate: Gate::try_from_weights(input_neurons, output_neurons, neuron_weights)?,
+            activation,
+            state: DVector::zeros(output_neurons),
+            scratch: GruScratch::new(output_neurons),
+        })
+    }
+
+    pub(crate) fn weight_count(input_neurons: usize, output_neurons: usize) -> Option<usize> {
+        Gate::<T>::weight_count(input_neurons, output_neurons)?.checked_mul(3)
+    }
+
+    /// The same layer, hidden state included, in another precision.
+    pub(crate) fn cast<U: Float>(&self) -> Gru<U> {
+        Gru {
+            update: self.update.cast(),
+            reset: self.reset.cast(),
+            candidate: self.candidate.cast(),
+            activation: self.activation,
+            state: self.state.map(|x| U::from_subset(&x.as_f64())),
+            scratch: GruScratch::new(self.state.len()),
+        }
+    }
+
+    pub(crate) fn weights(&self) -> impl Iterator<Item = T> + '_ {
+        self.update
+            .weights()
+            .chain(self.reset.weights())
+            .chain(self.candidate.weights())
+    }
+
+    pub(crate) fn reset_state(&mut self) {
+        self.state.fill(T::zero());
+    }
+
+    pub(crate) fn neurons(&self) -> usize {
+        self.state.len()
+    }
+
+    /// Computes the next hidden state without storing it, keeping the gate
+    /// outputs in `scratch`, which must be sized for this layer.
+    pub(crate) fn step_into(
+        &self,
+        inputs: &DVectorSlice<T>,
+        outputs: &mut DVectorSliceMut<T>,
+        scratch: &mut GruScratch<T>,
+    ) {
+        let sigmoid = |x: &mut T| *x = Activation::Sigmoid.apply(*x);
+
+        let mut update = scratch.update.rows_mut(0, self.state.len());
+        self.update
+            .preactivation_into(inputs, &self.state, &mut update);
+        update.apply(sigmoid);
+
+        let mut reset = scratch.reset.rows_mut(0, self.state.len());
+        self.reset
+            .preactivation_into(inputs, &self.state, &mut reset);
+        reset.apply(sigmoid);
+        reset.component_mul_assign(&self.state);
+
+        self.candidate
+            .preactivation_into(inputs, &scratch.reset, outputs);
+        outputs.apply(|x| *x = self.activation.apply(*x));
+
+        for ((output, z), h) in outputs
+            .iter_mut()
+            .zip(scratch.update.iter())
+            .zip(self.state.iter())
+        {
+            *output = (T::one() - *z) * *output + *z * *h;
+        }
+    }
+
+    pub(crate) fn propagate_into(
+        &mut self,
+        inputs: &DVectorSlice<T>,
+        outputs: &mut DVectorSliceMut<T>,
+    ) {
+        let mut scratch = std::mem::take(&mut self.scratch);
+        self.step_into(inputs, outputs, &mut scratch);
+        self.scratch = scratch;
+        self.state.copy_from(outputs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Elman, Gru, GruScratch};
+    use crate::{Activation, Initializer};
+    use nalgebra::{dvector, DVector};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn step(layer: &mut Elman<f32>, input: f32) -> f32 {
+        let mut output = DVector::zeros(1);
+        layer.propagate_into(&dvector![input].rows(0, 1), &mut output.rows_mut(0, 1));
+        output[0]
+    }
+
+    #[test_log::test]
+    fn elman_remembers_test() {
+        // h' = x + 0.5 h
+        let mut weights = vec![0.0, 1.0, 0.5].into_iter();
+        let mut layer = Elman::try_from_weights(1, 1, Activation::Identity, &mut weights).unwrap();
+
+        assert_eq!(step(&mut layer, 1.0), 1.0);
+        assert_eq!(step(&mut layer, 0.0), 0.5);
+        assert_eq!(step(&mut layer, 0.0), 0.25);
+
+        layer.reset_state();
+        assert_eq!(step(&mut layer, 0.0), 0.0);
+    }
+
+    #[test_log::test]
+    fn elman_lifecycle_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let layer: Elman<f32> =
+            Elman::random(4, 3, Activation::Tanh, Initializer::default(), &mut rng);
+        let second_layer =
+            Elman::try_from_weights(4, 3, Activation::Tanh, &mut layer.weights()).unwrap();
+
+        assert_eq!(
+            Some(layer.weights().count()),
+            Elman::<f32>::weight_count(4, 3)
+        );
+        assert_eq!(layer, second_layer);
+    }
+
+    #[test_log::test]
+    fn gru_lifecycle_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let layer: Gru<f32> = Gru::random(4, 3, Activation::Tanh, Initializer::default(), &mut rng);
+        let second_layer =
+            Gru::try_from_weights(4, 3, Activation::Tanh, &mut layer.weights()).unwrap();
+
+        assert_eq!(
+            Some(layer.weights().count()),
+            Gru::<f32>::weight_count(4, 3)
+        );
+        assert_eq!(layer, second_layer);
+    }
+
+    #[test_log::test]
+    fn gru_saturated_update_gate_holds_state_test() {
+        // update gate saturated at z = 1: the state never changes.
+        let update = vec![100.0, 0.0, 0.0];
+        let reset = vec![0.0, 0.0, 0.0];
+        let candidate = vec![0.0, 1.0, 0.0];
+        let mut weights = update.into_iter().chain(reset).chain(candidate);
+        let mut layer = Gru::try_from_weights(1, 1, Activation::Tanh, &mut weights).unwrap();
+        layer.state[0] = 0.75;
+
+        let mut output = DVector::zeros(1);
+        layer.propagate_into(&dvector![5.0].rows(0, 1), &mut output.rows_mut(0, 1));
+
+        assert_eq!(output[0], 0.75);
+        assert_eq!(layer.state[0], 0.75);
+    }
+
+    #[test_log::test]
+    fn gru_step_matches_propagate_test() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut layer = Gru::random(2, 3, Activation::Tanh, Initializer::default(), &mut rng);
+        let inputs = dvector![0.3, -0.7];
+        let mut scratch = GruScratch::new(3);
+
+        for _ in 0..3 {
+            let mut peeked = DVector::zeros(3);
+            layer.step_into(&inputs.rows(0, 2), &mut peeked.rows_mut(0, 3), &mut scratch);
+            let mut output = DVector::zeros(3);
+            layer.propagate_into(&inputs.rows(0, 2), &mut output.rows_mut(0, 3));
+
+            assert_eq!(peeked, output);
+            assert_eq!(layer.state, output);
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Diagrams of a [`Network`] as Graphviz DOT or standalone SVG.
+//!
+//! Every neuron becomes a node labelled with its bias and every weight an
+//! edge, blue for positive and red for negative, drawn thicker the larger its
+//! magnitude. Pruned connections are left out. [`Merge::Add`] skips are
+//! drawn as dashed unweighted edges; [`Merge::Concat`] skips have weights of
+//! their own and are drawn like any other connection.
+
+use std::fmt::Write;
+
+use crate::{Merge, Network, NetworkError, NetworkLayer};
+
+const POSITIVE: &str = "#1f77b4";
+const NEGATIVE: &str = "#d62728";
+const SKIP: &str = "#7f7f7f";
+
+/// SVG geometry, in pixels.
+const MARGIN: f32 = 40.0;
+const HEADER: f32 = 24.0;
+const COLUMN: f32 = 160.0;
+const ROW: f32 = 36.0;
+const RADIUS: f32 = 13.0;
+
+struct Column {
+    label: String,
+    /// `None` for the input layer.
+    biases: Vec<Option<f32>>,
+}
+
+struct Edge {
+    /// `(layer, neuron)` in topology order.
+    from: (usize, usize),
+    to: (usize, usize),
+    /// `None` for an unweighted [`Merge::Add`] skip.
+    weight: Option<f32>,
+}
+
+struct Diagram {
+    columns: Vec<Column>,
+    edges: Vec<Edge>,
+    largest: f32,
+}
+
+impl Diagram {
+    fn stroke(&self, weight: Option<f32>) -> (&'static str, f32) {
+        match weight {
+            Some(weight) => (
+                if weight < 0.0 { NEGATIVE } else { POSITIVE },
+                0.5 + 3.0 * weight.abs() / self.largest,
+            ),
+            None => (SKIP, 1.0),
+        }
+    }
+}
+
+impl Network {
+    /// Graphviz source with one cluster per layer, for `dot -Tsvg`.
+    /// Feed-forward and Hebbian layers can be drawn, the latter with the
+    /// weights they start each lifetime with.
+    pub fn to_dot(&self) -> Result<String, NetworkError> {
+        let diagram = self.diagram()?;
+
+        // writing into a String cannot fail
+        let mut dot = String::new();
+        let out = &mut dot;
+        writeln!(out, "digraph network {{").unwrap();
+        writeln!(out, "    rankdir=LR;").unwrap();
+        writeln!(out, "    splines=line;").unwrap();
+        writeln!(
+            out,
+            "    node [shape=circle, fixedsize=true, width=0.45, fontsize=9];"
+        )
+        .unwrap();
+        for (layer, column) in diagram.columns.iter().enumerate() {
+            writeln!(out).unwrap();
+            writeln!(out, "    subgraph cluster_{} {{", layer).unwrap();
+            writeln!(out, "        label=\"{}\";", column.label).unwrap();
+            writeln!(out, "        color=lightgray;").unwrap();
+            for (neuron, bias) in column.biases.iter().enumerate() {
+                let label = match bias {
+                    Some(bias) => format!("{:+.2}", bias),
+                    None => format!("x{}", neuron),
+                };
+                writeln!(out, "        n{}_{} [label=\"{}\"];", layer, neuron, label).unwrap();
+            }
+            writeln!(out, "    }}").unwrap();
+        }
+
+        writeln!(out).unwrap();
+        for edge in &diagram.edges {
+            let (color, width) = diagram.stroke(edge.weight);
+            let style = match edge.weight {
+                Some(weight) => format!("tooltip=\"{:+.4}\"", weight),
+                None => "style=dashed".to_owned(),
+            };
+            writeln!(
+                out,
+                "    n{}_{} -> n{}_{} [color=\"{}\", penwidth={:.2}, {}];",
+                edge.from.0, edge.from.1, edge.to.0, edge.to.1, color, width, style
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+
+        Ok(dot)
+    }
+
+    /// The same diagram as [`Network::to_dot`] laid out here, one column per
+    /// layer, as a self-contained SVG document.
+    pub fn to_svg(&self) -> Result<String, NetworkError> {
+        let diagram = self.diagram()?;
+        let tallest = diagram
+            .columns
+            .iter()
+            .map(|column| column.biases.len())
+            .max()
+            .unwrap_or(0);
+        let width = 2.0 * MARGIN + (diagram.columns.len() - 1) as f32 * COLUMN;
+        let height = 2.0 * MARGIN + HEADER + (tallest - 1) as f32 * ROW;
+        let position = |(layer, neuron): (usize, usize)| {
+            let offset = (tallest - diagram.columns[layer].biases.len()) as f32 * ROW / 2.0;
+            (
+                MARGIN + layer as f32 * COLUMN,
+                MARGIN + HEADER + offset + neuron as f32 * ROW,
+            )
+        };
+
+        let mut svg = String::new();
+        let out = &mut svg;
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\">",
+            width, height
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>"
+        )
+        .unwrap();
+
+        for edge in &diagram.edges {
+            let ((x1, y1), (x2, y2)) = (position(edge.from), position(edge.to));
+            let (color, width) = diagram.stroke(edge.weight);
+            let (dash, title) = match edge.weight {
+                Some(weight) => ("", format!("{:+.4}", weight)),
+                None => (" stroke-dasharray=\"4 3\"", "skip".to_owned()),
+            };
+            writeln!(
+                out,
+                "  <line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-width=\"{:.2}\" stroke-opacity=\"0.7\"{}><title>{}</title></line>",
+                x1, y1, x2, y2, color, width, dash, title
+            )
+            .unwrap();
+        }
+
+        for (layer, column) in diagram.columns.iter().enumerate() {
+            let x = MARGIN + layer as f32 * COLUMN;
+            writeln!(
+                out,
+                "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"12\" text-anchor=\"middle\">{}</text>",
+                x, MARGIN, column.label
+            )
+            .unwrap();
+            for (neuron, bias) in column.biases.iter().enumerate() {
+                let (x, y) = position((layer, neuron));
+                let label = match bias {
+                    Some(bias) => format!("{:+.2}", bias),
+                    None => format!("x{}", neuron),
+                };
+                writeln!(
+                    out,
+                    "  <circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"white\" stroke=\"black\"/>",
+                    x, y, RADIUS
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "  <text x=\"{:.1}\" y=\"{:.1}\" font-size=\"8\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+                    x, y, label
+                )
+                .unwrap();
+            }
+        }
+        writeln!(out, "</svg>").unwrap();
+
+        Ok(svg)
+    }
+
+    fn diagram(&self) -> Result<Diagram, NetworkError> {
+        let mut columns = vec![Column {
+            label: "input".to_owned(),
+            biases: vec![None; self.topology[0].neurons],
+        }];
+        let mut edges = Vec::new();
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let to = index + 1;
+            let shape = &self.topology[to];
+            let layer = match layer {
+                NetworkLayer::FeedForward(layer) => layer,
+                NetworkLayer::Hebbian(layer) => layer.evolved(),
+                _ => {
+                    return Err(NetworkError::UnsupportedLayer {
+                        layer: to,
+                        kind: shape.kind,
+                    })
+                }
+            };
+
+            // input columns past the previous layer are a concatenated skip
+            let previous = self.topology[index].neurons;
+            let source = |col: usize| match shape.skip {
+                Some(skip) if col >= previous => (skip.from, col - previous),
+                _ => (index, col),
+            };
+            let weights = layer.weight_matrix();
+            for row in 0..weights.nrows() {
+                for col in (0..weights.ncols()).filter(|&col| layer.is_connected(row, col)) {
+                    edges.push(Edge {
+                        from: source(col),
+                        to: (to, row),
+                        weight: Some(weights[(row, col)]),
+                    });
+                }
+            }
+            if let Some(skip) = shape.skip.filter(|skip| skip.merge == Merge::Add) {
+                // the skipped output joins the previous one, so it is drawn
+                // into whatever the previous neuron feeds
+                for neuron in 0..previous {
+                    edges.push(Edge {
+                        from: (skip.from, neuron),
+                        to: (index, neuron),
+                        weight: None,
+                    });
+                }
+            }
+
+            columns.push(Column {
+                label: format!("{:?}", layer.activation()),
+                biases: layer.biases().iter().map(|&bias| Some(bias)).collect(),
+            });
+        }
+
+        let largest = edges
+            .iter()
+            .filter_map(|edge| edge.weight)
+            .fold(0.0f32, |largest, weight| largest.max(weight.abs()));
+        Ok(Diagram {
+            columns,
+            edges,
+            largest: if largest > 0.0 { largest } else { 1.0 },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Activation, LayerKind, LayerTopology, Merge, Network, NetworkError, Skip};
+
+    fn network() -> Network {
+        let layers = &[
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::ReLU,
+                mask: Some(vec![true, true, false, true]),
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: Activation::Tanh,
+                skip: Some(Skip {
+                    from: 0,
+                    merge: Merge::Concat,
+                }),
+                ..Default::default()
+            },
+        ];
+        // neuron 1 of the hidden layer is cut from input 0
+        Network::from_weights(
+            layers,
+            vec![0.5, 1.0, -2.0, -0.25, 0.5, 0.0, 0.75, -0.5, 0.25, 1.5],
+        )
+    }
+
+    #[test]
+    fn test_dot_edges() {
+        let dot = network().to_dot().unwrap();
+
+        assert!(dot.starts_with("digraph network {"));
+        assert!(dot.contains("label=\"ReLU\";"));
+        assert!(dot.contains("n1_0 [label=\"+0.50\"];"));
+        assert!(dot.contains("n1_1 [label=\"-0.25\"];"));
+        assert!(
+            dot.contains("n0_1 -> n1_0 [color=\"#d62728\", penwidth=3.50, tooltip=\"-2.0000\"];")
+        );
+        assert!(!dot.contains("n0_0 -> n1_1"));
+        // concatenated skip inputs come from the input layer
+        assert!(dot.contains("n0_1 -> n2_0 [color=\"#1f77b4\""));
+        assert_eq!(dot.matches("->").count(), 3 + 4);
+    }
+
+    #[test]
+    fn test_svg_elements() {
+        let svg = network().to_svg().unwrap();
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<circle").count(), 5);
+        assert_eq!(svg.matches("<line").count(), 7);
+        assert!(svg.contains("<title>-2.0000</title>"));
+    }
+
+    #[test]
+    fn test_add_skip_and_unsupported_layers() {
+        let mut layers = vec![
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                skip: Some(Skip {
+                    from: 0,
+                    merge: Merge::Add,
+                }),
+                ..Default::default()
+            },
+        ];
+        let network: Network = Network::from_weights(&layers, vec![0.0, 1.0, 0.0, 1.0]);
+        let dot = network.to_dot().unwrap();
+        assert!(dot.contains("n0_0 -> n1_0 [color=\"#7f7f7f\", penwidth=1.00, style=dashed];"));
+
+        layers[1].kind = LayerKind::Gru;
+        let network: Network = Network::from_weights(&layers, vec![0.0; 11]);
+        assert_eq!(
+            network.to_svg(),
+            Err(NetworkError::UnsupportedLayer {
+                layer: 1,
+                kind: LayerKind::Gru
+            })
+        );
+    }
+}
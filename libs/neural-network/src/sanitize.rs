@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Float, NetworkError};
+
+/// What [`crate::Network::try_propagate`] does when a layer outputs `NaN` or
+/// an infinity.
+///
+/// Sanitized values are what the next layer sees, but a recurrent layer has
+/// already stored its raw output as its hidden state; call
+/// [`crate::Network::reset_state`] to clear it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum NonFinitePolicy {
+    /// Stop at the first non-finite activation and report where it came from.
+    #[default]
+    Error,
+    /// Hold every activation within `[-limit, limit]`, infinities included.
+    /// `NaN` becomes zero.
+    Clamp { limit: f32 },
+    /// Replace every non-finite activation by zero.
+    Zero,
+}
+
+impl NonFinitePolicy {
+    /// Applies the policy to the activations of topology layer `layer`.
+    pub fn apply<T: Float>(&self, layer: usize, values: &mut [T]) -> Result<(), NetworkError> {
+        match *self {
+            NonFinitePolicy::Error => match values.iter().position(|value| !value.is_finite()) {
+                Some(neuron) => Err(NetworkError::NonFiniteActivation {
+                    layer,
+                    neuron,
+                    value: values[neuron].as_f64(),
+                }),
+                None => Ok(()),
+            },
+            NonFinitePolicy::Clamp { limit } => {
+                let limit = T::cast_f32(limit);
+                for value in values {
+                    // comparisons with NaN are all false
+                    *value = if *value > limit {
+                        limit
+                    } else if *value < -limit {
+                        -limit
+                    } else if value.is_finite() {
+                        *value
+                    } else {
+                        T::zero()
+                    };
+                }
+                Ok(())
+            }
+            NonFinitePolicy::Zero => {
+                for value in values.iter_mut().filter(|value| !value.is_finite()) {
+                    *value = T::zero();
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NonFinitePolicy;
+    use crate::NetworkError;
+
+    const VALUES: [f32; 4] = [1.0, f32::NEG_INFINITY, -5.0, f32::NAN];
+
+    #[test]
+    fn test_error_policy() {
+        let mut values = VALUES;
+        assert_eq!(
+            NonFinitePolicy::Error.apply(2, &mut values),
+            Err(NetworkError::NonFiniteActivation {
+                layer: 2,
+                neuron: 1,
+                value: f64::NEG_INFINITY
+            })
+        );
+        assert_eq!(NonFinitePolicy::Error.apply(2, &mut [1.0, -5.0]), Ok(()));
+    }
+
+    #[test]
+    fn test_replacing_policies() {
+        let mut values = VALUES;
+        NonFinitePolicy::Clamp { limit: 2.0 }
+            .apply(0, &mut values)
+            .unwrap();
+        assert_eq!(values, [1.0, -2.0, -2.0, 0.0]);
+
+        let mut values = VALUES;
+        NonFinitePolicy::Zero.apply(0, &mut values).unwrap();
+        assert_eq!(values, [1.0, 0.0, -5.0, 0.0]);
+    }
+}
@@ -0,0 +1,300 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{Read, Write},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{LayerTopology, Network, NetworkError};
+
+/// Version of the serialized network layout. Bump whenever the meaning of the
+/// stored topology or weight order changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MAGIC: &[u8; 4] = b"LBNN";
+
+/// On-disk encoding of a [`Network`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// Human readable JSON document.
+    Json,
+    /// `LBNN` magic, little-endian `u32` version, then the bincode body.
+    Binary,
+}
+
+#[derive(Debug)]
+pub enum SerializationError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+    BadMagic,
+    UnsupportedVersion(u32),
+    Network(NetworkError),
+}
+
+impl Display for SerializationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SerializationError::Io(e) => write!(f, "SerializationError: io failure: {}", e),
+            SerializationError::Json(e) => write!(f, "SerializationError: invalid json: {}", e),
+            SerializationError::Binary(e) => {
+                write!(f, "SerializationError: invalid binary body: {}", e)
+            }
+            SerializationError::BadMagic => {
+                write!(f, "SerializationError: missing {:?} header", MAGIC)
+            }
+            SerializationError::UnsupportedVersion(version) => write!(
+                f,
+                "SerializationError: unsupported format version {} (expected {})",
+                version, FORMAT_VERSION
+            ),
+            SerializationError::Network(e) => {
+                write!(f, "SerializationError: stored network is invalid: {}", e)
+            }
+        }
+    }
+}
+
+impl Error for SerializationError {}
+
+impl From<std::io::Error> for SerializationError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for SerializationError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<NetworkError> for SerializationError {
+    fn from(e: NetworkError) -> Self {
+        Self::Network(e)
+    }
+}
+
+impl From<bincode::Error> for SerializationError {
+    fn from(e: bincode::Error) -> Self {
+        Self::Binary(e)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct NetworkFile {
+    version: u32,
+    #[serde(flatten)]
+    body: NetworkBody,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct NetworkBody {
+    topology: Vec<LayerTopology>,
+    weights: Vec<f32>,
+}
+
+// Files always hold `f32` weights; use `Network::cast` to save or load other
+// precisions.
+impl Network {
+    fn body(&self) -> NetworkBody {
+        NetworkBody {
+            topology: self.topology.clone(),
+            weights: self.weights().collect(),
+        }
+    }
+
+    fn from_body(body: NetworkBody) -> Result<Self, SerializationError> {
+        Ok(Self::try_from_weights(&body.topology, body.weights)?)
+    }
+
+    pub fn to_json(&self) -> Result<String, SerializationError> {
+        Ok(serde_json::to_string_pretty(&NetworkFile {
+            version: FORMAT_VERSION,
+            body: self.body(),
+        })?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, SerializationError> {
+        let header: Header = serde_json::from_str(json)?;
+        check_version(header.version)?;
+
+        let file: NetworkFile = serde_json::from_str(json)?;
+        Self::from_body(file.body)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SerializationError> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &self.body())?;
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SerializationError> {
+        if bytes.len() < 8 || &bytes[..4] != MAGIC {
+            return Err(SerializationError::BadMagic);
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        check_version(version)?;
+
+        let body: NetworkBody = bincode::deserialize(&bytes[8..])?;
+        Self::from_body(body)
+    }
+
+    pub fn save(&self, mut writer: impl Write, format: Format) -> Result<(), SerializationError> {
+        match format {
+            Format::Json => writer.write_all(self.to_json()?.as_bytes())?,
+            Format::Binary => writer.write_all(&self.to_bytes()?)?,
+        }
+        Ok(())
+    }
+
+    pub fn load(mut reader: impl Read, format: Format) -> Result<Self, SerializationError> {
+        match format {
+            Format::Json => {
+                let mut json = String::new();
+                reader.read_to_string(&mut json)?;
+                Self::from_json(&json)
+            }
+            Format::Binary => {
+                let mut bytes = Vec::new();
+                reader.read_to_end(&mut bytes)?;
+                Self::from_bytes(&bytes)
+            }
+        }
+    }
+}
+
+fn check_version(version: u32) -> Result<(), SerializationError> {
+    if version == FORMAT_VERSION {
+        Ok(())
+    } else {
+        Err(SerializationError::UnsupportedVersion(version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Format, SerializationError};
+    use crate::{Activation, LayerKind, LayerTopology, Network, NetworkError};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn network() -> Network {
+        let layers = &[
+            LayerTopology {
+                neurons: 3,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::LeakyReLU(0.01),
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 2,
+                activation: Activation::Tanh,
+                ..Default::default()
+            },
+        ];
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        Network::random(&mut rng, layers)
+    }
+
+    fn bits(network: &Network) -> Vec<u32> {
+        network.weights().map(f32::to_bits).collect()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let network = network();
+        let mut file = Vec::new();
+        network.save(&mut file, Format::Json).unwrap();
+        let loaded = Network::load(file.as_slice(), Format::Json).unwrap();
+
+        assert_eq!(bits(&network), bits(&loaded));
+        assert_eq!(network.topology(), loaded.topology());
+        assert_eq!(network.layers, loaded.layers);
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let network = network();
+        let mut file = Vec::new();
+        network.save(&mut file, Format::Binary).unwrap();
+        let loaded = Network::load(file.as_slice(), Format::Binary).unwrap();
+
+        assert_eq!(&file[..4], b"LBNN");
+        assert_eq!(bits(&network), bits(&loaded));
+        assert_eq!(network.layers, loaded.layers);
+    }
+
+    #[test]
+    fn test_rejects_other_versions() {
+        let json = network()
+            .to_json()
+            .unwrap()
+            .replacen("\"version\": 1", "\"version\": 99", 1);
+        assert!(matches!(
+            Network::from_json(&json),
+            Err(SerializationError::UnsupportedVersion(99))
+        ));
+
+        let mut bytes = network().to_bytes().unwrap();
+        bytes[4] = 99;
+        assert!(matches!(
+            Network::from_bytes(&bytes),
+            Err(SerializationError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn test_rejects_truncated_weights() {
+        let json = r#"{
+            "version": 1,
+            "topology": [
+                { "neurons": 1, "activation": "Identity" },
+                { "neurons": 1, "activation": "Tanh" }
+            ],
+            "weights": [0.5]
+        }"#;
+        assert!(matches!(
+            Network::from_json(json),
+            Err(SerializationError::Network(
+                NetworkError::TooFewWeights { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_layer_kind_defaults_to_feed_forward() {
+        let json = r#"{
+            "version": 1,
+            "topology": [
+                { "neurons": 1, "activation": "Identity" },
+                { "neurons": 1, "activation": "Identity" }
+            ],
+            "weights": [0.5, 2.0]
+        }"#;
+        let mut network = Network::from_json(json).unwrap();
+
+        assert_eq!(network.topology()[1].kind, LayerKind::FeedForward);
+        assert_eq!(network.propagate(vec![1.0]), vec![2.5]);
+        assert_eq!(network.propagate(vec![1.0]), vec![2.5]);
+    }
+
+    #[test]
+    fn test_rejects_missing_magic() {
+        assert!(matches!(
+            Network::from_bytes(b"nope"),
+            Err(SerializationError::BadMagic)
+        ));
+    }
+}
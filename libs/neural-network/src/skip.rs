@@ -0,0 +1,102 @@
+use std::borrow::Cow;
+
+use nalgebra::{
+    allocator::{Allocator, Reallocator},
+    DefaultAllocator, Dim, Dynamic, OMatrix,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{Float, LayerTopology};
+
+/// Feeds the output of an earlier layer into a later layer, next to the
+/// output of the layer right before it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Skip {
+    /// Index into the topology of the layer whose output is fed forward; `0`
+    /// is the network input. Must be before the previous layer.
+    pub from: usize,
+    pub merge: Merge,
+}
+
+/// How a [`Skip`] combines the skipped output with the previous layer's.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Merge {
+    /// Element-wise sum, so both layers must be equally wide. The receiving
+    /// layer keeps its weight count, making this a residual connection.
+    Add,
+    /// The skipped output is appended below the previous output, so the
+    /// receiving layer has a weight for every input of either. A convolution
+    /// cannot receive one, as it would read the skipped values as part of
+    /// its input channels.
+    Concat,
+}
+
+impl Merge {
+    /// Width of the merged input.
+    pub(crate) fn width(self, previous: usize, skipped: usize) -> usize {
+        match self {
+            Merge::Add => previous,
+            Merge::Concat => previous + skipped,
+        }
+    }
+
+    pub(crate) fn apply<T: Float, C: Dim>(
+        self,
+        previous: OMatrix<T, Dynamic, C>,
+        skipped: &OMatrix<T, Dynamic, C>,
+    ) -> OMatrix<T, Dynamic, C>
+    where
+        DefaultAllocator: Allocator<T, Dynamic, C> + Reallocator<T, Dynamic, C, Dynamic, C>,
+    {
+        match self {
+            Merge::Add => previous + skipped,
+            Merge::Concat => {
+                let rows = previous.nrows();
+                let mut merged = previous.resize_vertically(rows + skipped.nrows(), T::zero());
+                merged.rows_mut(rows, skipped.nrows()).copy_from(skipped);
+                merged
+            }
+        }
+    }
+}
+
+/// What layer `layer` of `topology` receives as its input: the previous
+/// layer, widened by a [`Merge::Concat`] skip. Only its `neurons` and `kind`
+/// are meaningful.
+pub(crate) fn input_topology(topology: &[LayerTopology], layer: usize) -> Cow<'_, LayerTopology> {
+    let previous = &topology[layer - 1];
+    match topology[layer].skip {
+        Some(Skip {
+            from,
+            merge: Merge::Concat,
+        }) => Cow::Owned(LayerTopology {
+            neurons: previous.neurons.saturating_add(topology[from].neurons),
+            kind: previous.kind,
+            ..Default::default()
+        }),
+        _ => Cow::Borrowed(previous),
+    }
+}
+
+/// Whether any layer takes a skip connection from `layer`.
+pub(crate) fn is_skipped(topology: &[LayerTopology], layer: usize) -> bool {
+    topology
+        .iter()
+        .any(|other| other.skip.map(|skip| skip.from) == Some(layer))
+}
+
+/// Input of the layer following `outputs.last()`, given the outputs of every
+/// layer so far.
+pub(crate) fn layer_input<T: Float, C: Dim>(
+    outputs: &[OMatrix<T, Dynamic, C>],
+    skip: Option<Skip>,
+) -> Cow<'_, OMatrix<T, Dynamic, C>>
+where
+    DefaultAllocator: Allocator<T, Dynamic, C> + Reallocator<T, Dynamic, C, Dynamic, C>,
+{
+    let previous = outputs.last().expect("the network input is always present");
+    match skip {
+        None => Cow::Borrowed(previous),
+        Some(skip) => Cow::Owned(skip.merge.apply(previous.clone(), &outputs[skip.from])),
+    }
+}
@@ -0,0 +1,500 @@
+//! Supervised training of feed-forward networks by backpropagation.
+
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use crate::{skip, Float, Merge, Network, NetworkError, NetworkLayer, Skip};
+
+/// An `(input, target)` pair.
+pub type Sample<T = f32> = (Vec<T>, Vec<T>);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Loss {
+    /// Squared error averaged over outputs and samples.
+    #[default]
+    MeanSquaredError,
+    /// Binary cross-entropy averaged over outputs and samples; outputs are
+    /// read as probabilities, so pair it with a [`crate::Activation::Sigmoid`]
+    /// output layer.
+    CrossEntropy,
+}
+
+impl Loss {
+    const EPSILON: f32 = 1e-7;
+
+    fn value<T: Float>(&self, output: T, target: T) -> T {
+        let one = T::one();
+        match self {
+            Loss::MeanSquaredError => (output - target).powi(2),
+            Loss::CrossEntropy => {
+                let p = Self::clamp(output);
+                -(target * p.ln() + (one - target) * (one - p).ln())
+            }
+        }
+    }
+
+    fn derivative<T: Float>(&self, output: T, target: T) -> T {
+        let one = T::one();
+        match self {
+            Loss::MeanSquaredError => (one + one) * (output - target),
+            Loss::CrossEntropy => {
+                let p = Self::clamp(output);
+                (p - target) / (p * (one - p))
+            }
+        }
+    }
+
+    fn clamp<T: Float>(output: T) -> T {
+        let epsilon = T::cast_f32(Self::EPSILON);
+        output.clamp(epsilon, T::one() - epsilon)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Optimizer {
+    Sgd {
+        learning_rate: f32,
+    },
+    Momentum {
+        learning_rate: f32,
+        momentum: f32,
+    },
+    Adam {
+        learning_rate: f32,
+        beta1: f32,
+        beta2: f32,
+        epsilon: f32,
+    },
+}
+
+impl Optimizer {
+    pub fn sgd(learning_rate: f32) -> Self {
+        Self::Sgd { learning_rate }
+    }
+
+    pub fn momentum(learning_rate: f32, momentum: f32) -> Self {
+        Self::Momentum {
+            learning_rate,
+            momentum,
+        }
+    }
+
+    /// Adam with the usual `beta1 = 0.9`, `beta2 = 0.999`, `epsilon = 1e-8`.
+    pub fn adam(learning_rate: f32) -> Self {
+        Self::Adam {
+            learning_rate,
+            beta1: 0.9,
+            beta2: 0.999,
+            epsilon: 1e-8,
+        }
+    }
+}
+
+/// Updates a [`Network`] in place from batches of samples. The optimizer
+/// state (velocities, moment estimates) lives here, so keep one trainer per
+/// network for the whole run.
+#[derive(Clone, Debug)]
+pub struct Trainer<T: Float = f32> {
+    loss: Loss,
+    optimizer: Optimizer,
+    first_moment: Vec<T>,
+    second_moment: Vec<T>,
+    step: i32,
+}
+
+impl<T: Float> Trainer<T> {
+    pub fn new(loss: Loss, optimizer: Optimizer) -> Self {
+        Self {
+            loss,
+            optimizer,
+            first_moment: Vec::new(),
+            second_moment: Vec::new(),
+            step: 0,
+        }
+    }
+
+    pub fn loss(&self) -> Loss {
+        self.loss
+    }
+
+    pub fn optimizer(&self) -> Optimizer {
+        self.optimizer
+    }
+
+    /// Takes one optimizer step on `batch` and returns the loss measured
+    /// before the step. Fails without touching `network` if it contains a
+    /// layer backpropagation does not support.
+    pub fn train_batch(
+        &mut self,
+        network: &mut Network<T>,
+        batch: &[Sample<T>],
+    ) -> Result<T, NetworkError> {
+        let (loss, gradients) = network.gradients(self.loss, batch)?;
+
+        if self.first_moment.len() != gradients.len() {
+            self.first_moment = vec![T::zero(); gradients.len()];
+            self.second_moment = vec![T::zero(); gradients.len()];
+            self.step = 0;
+        }
+        self.step += 1;
+
+        let one = T::one();
+        let deltas: Vec<T> = match self.optimizer {
+            Optimizer::Sgd { learning_rate } => {
+                let learning_rate = T::cast_f32(learning_rate);
+                gradients.iter().map(|&g| -learning_rate * g).collect()
+            }
+            Optimizer::Momentum {
+                learning_rate,
+                momentum,
+            } => {
+                let (learning_rate, momentum) = (T::cast_f32(learning_rate), T::cast_f32(momentum));
+                self.first_moment
+                    .iter_mut()
+                    .zip(&gradients)
+                    .map(|(velocity, &g)| {
+                        *velocity = momentum * *velocity - learning_rate * g;
+                        *velocity
+                    })
+                    .collect()
+            }
+            Optimizer::Adam {
+                learning_rate,
+                beta1,
+                beta2,
+                epsilon,
+            } => {
+                let learning_rate = T::cast_f32(learning_rate);
+                let (beta1, beta2) = (T::cast_f32(beta1), T::cast_f32(beta2));
+                let epsilon = T::cast_f32(epsilon);
+                let first_correction = one - beta1.powi(self.step);
+                let second_correction = one - beta2.powi(self.step);
+                self.first_moment
+                    .iter_mut()
+                    .zip(self.second_moment.iter_mut())
+                    .zip(&gradients)
+                    .map(|((m, v), &g)| {
+                        *m = beta1 * *m + (one - beta1) * g;
+                        *v = beta2 * *v + (one - beta2) * g * g;
+                        let m = *m / first_correction;
+                        let v = *v / second_correction;
+                        -learning_rate * m / (v.sqrt() + epsilon)
+                    })
+                    .collect()
+            }
+        };
+
+        let mut deltas = deltas.into_iter();
+        for layer in &mut network.layers {
+            if let NetworkLayer::FeedForward(layer) = layer {
+                layer.add_deltas(&mut deltas);
+            }
+        }
+        Ok(loss)
+    }
+}
+
+impl<T: Float> Network<T> {
+    /// Mean `loss` over `batch` and its gradient with respect to every
+    /// weight, in [`Network::weights`] order. Only feed-forward layers are
+    /// supported, with or without skip connections.
+    pub fn gradients(&self, loss: Loss, batch: &[Sample<T>]) -> Result<(T, Vec<T>), NetworkError> {
+        let layers = self
+            .layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| match layer {
+                NetworkLayer::FeedForward(layer) => Ok(layer),
+                _ => Err(NetworkError::UnsupportedLayer {
+                    layer: index + 1,
+                    kind: self.topology[index + 1].kind,
+                }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let inputs = self.topology[0].neurons;
+        let outputs = self.topology[self.topology.len() - 1].neurons;
+        if batch.is_empty() {
+            return Ok((
+                T::zero(),
+                vec![T::zero(); Network::weight_count(&self.topology)],
+            ));
+        }
+        for (input, target) in batch {
+            assert_eq!(input.len(), inputs);
+            assert_eq!(target.len(), outputs);
+        }
+
+        // forward pass, keeping every layer's output, merged input and
+        // preactivation
+        let mut activations = vec![DMatrix::from_fn(inputs, batch.len(), |row, col| {
+            batch[col].0[row]
+        })];
+        let mut layer_inputs = Vec::with_capacity(layers.len());
+        let mut preactivations = Vec::with_capacity(layers.len());
+        for (layer, shape) in layers.iter().zip(&self.topology[1..]) {
+            let input = skip::layer_input(&activations, shape.skip).into_owned();
+            let z = layer.preactivate_batch(&input);
+            let activation = layer.activation();
+            activations.push(z.map(|x| activation.apply(x)));
+            layer_inputs.push(input);
+            preactivations.push(z);
+        }
+
+        let scale = T::one() / T::from_subset(&((outputs * batch.len()) as f64));
+        let predictions = activations.pop().unwrap();
+        let targets = DMatrix::from_fn(outputs, batch.len(), |row, col| batch[col].1[row]);
+        let value = predictions.zip_fold(&targets, T::zero(), |sum, output, target| {
+            sum + loss.value(output, target)
+        }) * scale;
+        // gradient of the loss with respect to each layer's output; a skipped
+        // layer collects it from both of its readers
+        let mut upstreams: Vec<Option<DMatrix<T>>> = vec![None; layers.len() + 1];
+        upstreams[layers.len()] = Some(predictions.zip_map(&targets, |output, target| {
+            loss.derivative(output, target) * scale
+        }));
+
+        // backward pass, collecting each layer's gradient in weights() order
+        let mut gradients = Vec::with_capacity(layers.len());
+        for (index, ((layer, z), input)) in layers
+            .iter()
+            .zip(&preactivations)
+            .zip(&layer_inputs)
+            .enumerate()
+            .rev()
+        {
+            let upstream = upstreams[index + 1]
+                .take()
+                .expect("every layer output feeds a later layer");
+            let activation = layer.activation();
+            let delta = upstream.zip_map(z, |d, x| d * activation.derivative(x));
+            let weight_gradient = &delta * input.transpose();
+            let bias_gradient = delta.column_sum();
+
+            let mut layer_gradient =
+                Vec::with_capacity(weight_gradient.len() + bias_gradient.len());
+            for row in 0..bias_gradient.len() {
+                layer_gradient.push(bias_gradient[row]);
+                layer_gradient.extend(
+                    (0..weight_gradient.ncols())
+                        .filter(|&col| layer.is_connected(row, col))
+                        .map(|col| weight_gradient[(row, col)]),
+                );
+            }
+            gradients.push(layer_gradient);
+
+            let input_gradient = layer.weight_matrix().transpose() * delta;
+            let previous = self.topology[index].neurons;
+            match self.topology[index + 1].skip {
+                None => accumulate(&mut upstreams[index], input_gradient),
+                Some(Skip { from, merge }) => {
+                    let skipped = match merge {
+                        Merge::Add => input_gradient.clone(),
+                        Merge::Concat => input_gradient
+                            .rows(previous, input.nrows() - previous)
+                            .into(),
+                    };
+                    accumulate(
+                        &mut upstreams[index],
+                        input_gradient.rows(0, previous).into(),
+                    );
+                    accumulate(&mut upstreams[from], skipped);
+                }
+            }
+        }
+
+        Ok((value, gradients.into_iter().rev().flatten().collect()))
+    }
+}
+
+fn accumulate<T: Float>(total: &mut Option<DMatrix<T>>, gradient: DMatrix<T>) {
+    match total {
+        Some(total) => *total += gradient,
+        None => *total = Some(gradient),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Loss, Optimizer, Trainer};
+    use crate::{Activation, LayerKind, LayerTopology, Merge, Network, NetworkError, Skip};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn topology(output: Activation) -> Vec<LayerTopology> {
+        vec![
+            LayerTopology {
+                neurons: 2,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 4,
+                activation: Activation::Tanh,
+                ..Default::default()
+            },
+            LayerTopology {
+                neurons: 1,
+                activation: output,
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn samples(f: impl Fn(f32, f32) -> f32) -> Vec<(Vec<f32>, Vec<f32>)> {
+        let points = [-1.0, -0.5, 0.0, 0.5, 1.0];
+        points
+            .iter()
+            .flat_map(|&a| points.iter().map(move |&b| (a, b)))
+            .map(|(a, b)| (vec![a, b], vec![f(a, b)]))
+            .collect()
+    }
+
+    #[test]
+    fn test_gradients_match_finite_differences() {
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let batch = samples(|a, b| (a * b > 0.0) as u8 as f32);
+
+        for (loss, output, skip) in [
+            (Loss::MeanSquaredError, Activation::Identity, None),
+            (Loss::CrossEntropy, Activation::Sigmoid, None),
+            (
+                Loss::MeanSquaredError,
+                Activation::Identity,
+                Some(Merge::Concat),
+            ),
+            (Loss::MeanSquaredError, Activation::Tanh, Some(Merge::Add)),
+        ] {
+            let mut layers = topology(output);
+            if let Some(merge) = skip {
+                // a second hidden layer, skipped over by the output layer
+                layers.insert(
+                    2,
+                    LayerTopology {
+                        neurons: 2,
+                        activation: Activation::Tanh,
+                        ..Default::default()
+                    },
+                );
+                layers[3].skip = Some(Skip { from: 1, merge });
+                if merge == Merge::Add {
+                    layers[1].neurons = 2;
+                }
+            }
+            let network = Network::random(&mut rng, &layers);
+            let (_, gradients) = network.gradients(loss, &batch).unwrap();
+            let weights: Vec<_> = network.weights().collect();
+            assert_eq!(gradients.len(), weights.len());
+
+            let h = 1e-2;
+            for index in 0..weights.len() {
+                let nudged = |by: f32| {
+                    let mut weights = weights.clone();
+                    weights[index] += by;
+                    Network::from_weights(&layers, weights)
+                        .gradients(loss, &batch)
+                        .unwrap()
+                        .0
+                };
+                let numeric = (nudged(h) - nudged(-h)) / (2.0 * h);
+                assert!(
+                    (numeric - gradients[index]).abs() < 1e-2,
+                    "{:?} weight {}: {} vs {}",
+                    loss,
+                    index,
+                    numeric,
+                    gradients[index]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_optimizers_reduce_loss() {
+        let batch = samples(|a, b| 0.5 * a - b);
+        for optimizer in [
+            Optimizer::sgd(0.1),
+            Optimizer::momentum(0.05, 0.9),
+            Optimizer::adam(0.02),
+        ] {
+            let mut rng = ChaCha8Rng::from_seed(Default::default());
+            let mut network = Network::random(&mut rng, &topology(Activation::Identity));
+            let mut trainer = Trainer::new(Loss::MeanSquaredError, optimizer);
+
+            let initial = trainer.train_batch(&mut network, &batch).unwrap();
+            for _ in 0..300 {
+                trainer.train_batch(&mut network, &batch).unwrap();
+            }
+            let trained = trainer.train_batch(&mut network, &batch).unwrap();
+
+            assert!(
+                trained < 0.01,
+                "{:?}: {} -> {}",
+                optimizer,
+                initial,
+                trained
+            );
+        }
+    }
+
+    #[test]
+    fn test_cross_entropy_classifies() {
+        let batch = samples(|a, _| (a > 0.0) as u8 as f32);
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &topology(Activation::Sigmoid));
+        let mut trainer = Trainer::new(Loss::CrossEntropy, Optimizer::adam(0.05));
+
+        for _ in 0..200 {
+            trainer.train_batch(&mut network, &batch).unwrap();
+        }
+
+        for (input, target) in &batch {
+            if input[0] != 0.0 {
+                let output = network.propagate(input.clone())[0];
+                assert_eq!(output > 0.5, target[0] > 0.5, "{:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pruned_connections_stay_cut() {
+        let batch = samples(|a, b| a - b);
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &topology(Activation::Identity));
+        network.prune(0.5);
+        let topology = network.topology().to_vec();
+        let mut trainer = Trainer::new(Loss::MeanSquaredError, Optimizer::adam(0.02));
+
+        for _ in 0..10 {
+            trainer.train_batch(&mut network, &batch).unwrap();
+        }
+
+        let (_, gradients) = network.gradients(Loss::MeanSquaredError, &batch).unwrap();
+        assert_eq!(gradients.len(), network.weights().count());
+        assert_eq!(network.topology(), &topology[..]);
+        assert_eq!(
+            Network::from_weights(&topology, network.weights())
+                .weights()
+                .collect::<Vec<_>>(),
+            network.weights().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_recurrent_layer_unsupported() {
+        let mut layers = topology(Activation::Identity);
+        layers[1].kind = LayerKind::Elman;
+        let mut rng = ChaCha8Rng::from_seed(Default::default());
+        let mut network = Network::random(&mut rng, &layers);
+        let weights: Vec<_> = network.weights().collect();
+        let mut trainer = Trainer::new(Loss::MeanSquaredError, Optimizer::sgd(0.1));
+
+        assert_eq!(
+            trainer.train_batch(&mut network, &samples(|a, b| a + b)),
+            Err(NetworkError::UnsupportedLayer {
+                layer: 1,
+                kind: LayerKind::Elman
+            })
+        );
+        assert_eq!(network.weights().collect::<Vec<_>>(), weights);
+    }
+}
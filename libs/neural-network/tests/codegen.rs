@@ -0,0 +1,63 @@
+use lib_neural_network::{Activation, LayerTopology, Network};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+// Output of `network().to_rust()`, checked in so the generated code is
+// compiled as part of this test.
+#[allow(dead_code)]
+#[path = "codegen/network.rs"]
+mod generated;
+
+fn network() -> Network {
+    let layers = &[
+        LayerTopology {
+            neurons: 3,
+            ..Default::default()
+        },
+        LayerTopology {
+            neurons: 6,
+            activation: Activation::ReLU,
+            ..Default::default()
+        },
+        LayerTopology {
+            neurons: 4,
+            activation: Activation::Sigmoid,
+            ..Default::default()
+        },
+        LayerTopology {
+            neurons: 2,
+            activation: Activation::Tanh,
+            ..Default::default()
+        },
+    ];
+    let weights =
+        (0..Network::weight_count(layers)).map(|i| ((i * 7919) % 200) as f32 / 100.0 - 1.0);
+    Network::from_weights(layers, weights)
+}
+
+#[test]
+fn generated_source_is_up_to_date() {
+    assert_eq!(
+        network().to_rust().unwrap(),
+        include_str!("codegen/network.rs"),
+        "regenerate tests/codegen/network.rs from `network().to_rust()`"
+    );
+}
+
+#[test]
+fn generated_propagate_matches_network() {
+    let mut network = network();
+    let mut rng = ChaCha8Rng::from_seed(Default::default());
+
+    for _ in 0..100 {
+        let input: [f32; 3] = rng.gen();
+        let input = input.map(|x| 4.0 * x - 2.0);
+
+        let expected = network.propagate(input.to_vec());
+        let actual = generated::propagate(&input);
+
+        for (expected, actual) in expected.iter().zip(&actual) {
+            assert!((expected - actual).abs() < 1e-6, "{:?}", input);
+        }
+    }
+}
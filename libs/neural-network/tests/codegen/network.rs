@@ -0,0 +1,52 @@
+// Generated by lib-neural-network, do not edit.
+// Layers: 3 -> 6 -> 4 -> 2
+
+pub const INPUTS: usize = 3;
+pub const OUTPUTS: usize = 2;
+
+const W0: [[f32; 3]; 6] = [
+    [0.19000006, -0.62, 0.57000005],
+    [0.95000005, 0.13999999, -0.66999996],
+    [-0.29000002, 0.9, 0.09000003],
+    [0.47000003, -0.33999997, 0.85],
+    [-0.77, 0.41999996, -0.39],
+    [-0.00999999, -0.82, 0.37],
+];
+const B0: [f32; 6] = [-1.0, -0.24000001, 0.52, -0.72, 0.03999996, 0.79999995];
+
+const W1: [[f32; 6]; 4] = [
+    [0.75, -0.060000002, -0.87, 0.32000005, -0.49, 0.70000005],
+    [-0.92, 0.26999998, -0.53999996, 0.65, -0.16000003, -0.97],
+    [-0.59000003, 0.6, -0.20999998, 0.98, 0.16999996, -0.64],
+    [-0.26, 0.92999995, 0.120000005, -0.69, 0.5, -0.31],
+];
+const B1: [f32; 4] = [-0.44, -0.110000014, 0.22000003, 0.54999995];
+
+const W2: [[f32; 4]; 2] = [
+    [0.07000005, -0.74, 0.45000005, -0.36],
+    [0.01999998, -0.79, 0.39999998, -0.41000003],
+];
+const B2: [f32; 2] = [0.88, 0.83000004];
+
+pub fn propagate(input: &[f32; INPUTS]) -> [f32; OUTPUTS] {
+    let hidden0 = dense(input, &W0, &B0, |x| x.max(0.0));
+    let hidden1 = dense(&hidden0, &W1, &B1, |x| 1.0 / (1.0 + (-x).exp()));
+    dense(&hidden1, &W2, &B2, f32::tanh)
+}
+
+fn dense<const I: usize, const O: usize>(
+    input: &[f32; I],
+    weights: &[[f32; I]; O],
+    biases: &[f32; O],
+    activation: fn(f32) -> f32,
+) -> [f32; O] {
+    let mut output = [0.0; O];
+    for ((output, row), bias) in output.iter_mut().zip(weights).zip(biases) {
+        let mut sum = 0.0;
+        for (weight, x) in row.iter().zip(input) {
+            sum += weight * x;
+        }
+        *output = activation(sum + bias);
+    }
+    output
+}
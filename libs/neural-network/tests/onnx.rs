@@ -0,0 +1,35 @@
+use lib_neural_network::{Activation, LayerTopology, Network};
+
+fn network() -> Network {
+    let layers = &[
+        LayerTopology {
+            neurons: 2,
+            ..Default::default()
+        },
+        LayerTopology {
+            neurons: 2,
+            activation: Activation::LeakyReLU(0.0),
+            ..Default::default()
+        },
+        LayerTopology {
+            neurons: 1,
+            activation: Activation::Tanh,
+            ..Default::default()
+        },
+    ];
+    let weights = vec![0.5, 1.0, -2.0, -0.25, 0.75, 1.5, 0.125, -1.0, 2.0];
+    Network::from_weights(layers, weights)
+}
+
+// `onnx/leaky_relu.onnx` is `protoc`'s encoding of the model in
+// `onnx/leaky_relu.textproto` against the upstream `onnx.proto`, so it does
+// not share the exporter's hand-written schema. A zero `alpha` must still be
+// written, or runtimes fall back to their default slope of 0.01.
+#[test]
+fn export_matches_reference_encoding() {
+    assert_eq!(
+        network().to_onnx().unwrap(),
+        include_bytes!("onnx/leaky_relu.onnx"),
+        "the fixture records the crate version; update and regenerate it on a bump"
+    );
+}
@@ -31,9 +31,24 @@ impl Simulation {
     #[wasm_bindgen(constructor)]
     pub fn new(generation_id: String, fitness_id: String) -> Self {
         let _ = Timer::new("Simulation::new");
+        Self::build(generation_id, fitness_id, sim::Simulation::random)
+    }
+
+    /// A simulation whose birds carry NEAT brains, see
+    /// `lib_simulation::Simulation::random_neat`.
+    pub fn new_neat(generation_id: String, fitness_id: String) -> Self {
+        let _ = Timer::new("Simulation::new_neat");
+        Self::build(generation_id, fitness_id, sim::Simulation::random_neat)
+    }
+
+    fn build(
+        generation_id: String,
+        fitness_id: String,
+        random: fn(&mut dyn RngCore, Box<dyn sim::Observer<f32>>) -> sim::Simulation,
+    ) -> Self {
         let mut rng = thread_rng();
         let generation_observer = GenerationObserver::new(generation_id.to_owned());
-        let sim = sim::Simulation::random(
+        let sim = random(
             &mut rng,
             Box::new(FitnessObserver::new(
                 fitness_id.to_owned(),
@@ -48,6 +63,36 @@ impl Simulation {
         JsValue::from_serde(&world).expect("failed to serialize world")
     }
 
+    /// Records the activity of the `index`-th animal from the next step on,
+    /// or of no animal for `undefined`.
+    pub fn select(&mut self, index: Option<usize>) {
+        self.sim.select(index);
+    }
+
+    /// Layer-by-layer neuron activations of the `index`-th animal during the
+    /// last step, as an array of arrays. Empty unless the animal was selected,
+    /// `null` if there is no such animal.
+    pub fn activity(&self, index: usize) -> JsValue {
+        match self.sim.world().animals().get(index) {
+            Some(animal) => serde_wasm_bindgen::to_value(animal.activity())
+                .expect("failed to serialize activity"),
+            None => JsValue::NULL,
+        }
+    }
+
+    /// How closely an int8 copy of the best bird's brain tracks the original,
+    /// as a human readable line.
+    pub fn quantization_report(&self) -> Option<String> {
+        self.sim
+            .quantization_report()
+            .map(|report| report.to_string())
+    }
+
+    /// SVG diagram of the best fed bird's brain.
+    pub fn champion_svg(&self) -> Option<String> {
+        self.sim.champion_svg()
+    }
+
     pub fn age(&self) -> usize {
         self.sim.age
     }
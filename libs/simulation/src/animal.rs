@@ -6,40 +6,63 @@ pub struct Animal {
     pub(crate) rotation: na::Rotation2<f32>,
     pub(crate) speed: f32,
     pub(crate) eye: Eye,
-    pub(crate) brain: Brain,
+    pub(crate) brain: Box<dyn Controller>,
     pub(crate) satiation: usize,
+    /// What the eye saw last step.
+    pub(crate) vision: Vec<f32>,
+    pub(crate) activity: Vec<Vec<f32>>,
 }
 
 impl Animal {
-    pub(crate) fn random(rng: &mut dyn RngCore) -> Self {
+    pub(crate) fn random(rng: &mut dyn RngCore, hidden: nn::LayerKind) -> Self {
         let eye = Eye::default();
-        let brain = Brain::random(rng, &eye);
-        Self {
-            position: rng.gen(),
-            rotation: rng.gen(),
-            speed: 0.002,
-            eye,
-            brain,
-            satiation: 0,
+        let brain = Brain::random(rng, &eye, hidden);
+
+        Self::new(eye, Box::new(brain), rng)
+    }
+
+    /// Motor outputs for `observation`. The activity is only recorded when
+    /// `traced`, and is cleared otherwise or on error.
+    pub(crate) fn see(
+        &mut self,
+        observation: &Observation,
+        policy: nn::NonFinitePolicy,
+        traced: bool,
+    ) -> Result<Vec<f32>, nn::NetworkError> {
+        self.activity.clear();
+        if !traced {
+            return self.brain.observe(observation, policy);
         }
+
+        self.activity = self.brain.observe_traced(observation, policy)?;
+        Ok(self.activity.last().unwrap().clone())
     }
 
-    pub(crate) fn see(&self, vision: Vec<f32>) -> Vec<f32> {
-        self.brain.nn.propagate(vision)
+    /// `None` unless the controller is bred from chromosomes.
+    pub(crate) fn as_chromosome(&self) -> Option<ga::Chromosome> {
+        self.brain.encode()
     }
 
-    pub(crate) fn as_chromosome(&self) -> ga::Chromosome {
-        self.brain.as_chromosome()
+    /// A bird driven by a controller shaped like `parent`'s, holding
+    /// `chromosome`. `None` unless `parent` is bred from chromosomes.
+    pub(crate) fn from_chromosome(
+        chromosome: ga::Chromosome,
+        parent: &dyn Controller,
+        rng: &mut dyn RngCore,
+    ) -> Option<Self> {
+        let brain = parent.decode(chromosome)?;
+
+        Some(Self::new(Eye::default(), brain, rng))
     }
 
-    pub(crate) fn from_chromosome(chromosome: ga::Chromosome, rng: &mut dyn RngCore) -> Self {
+    pub(crate) fn from_genome(genome: &nn::neat::Genome, rng: &mut dyn RngCore) -> Self {
         let eye = Eye::default();
-        let brain = Brain::from_chromosome(chromosome, &eye);
+        let brain = Brain::from_genome(genome);
 
-        Self::new(eye, brain, rng) 
+        Self::new(eye, Box::new(brain), rng)
     }
 
-    fn new(eye: Eye, brain: Brain, rng: &mut dyn RngCore) -> Self {
+    fn new(eye: Eye, brain: Box<dyn Controller>, rng: &mut dyn RngCore) -> Self {
         Self {
             position: rng.gen(),
             rotation: rng.gen(),
@@ -47,6 +70,8 @@ impl Animal {
             eye,
             brain,
             satiation: 0,
+            vision: Vec::new(),
+            activity: Vec::new(),
         }
     }
 
@@ -57,4 +82,11 @@ impl Animal {
     pub fn rotation(&self) -> na::Rotation2<f32> {
         self.rotation
     }
+
+    /// Neuron activations of the last step, one `Vec` per brain layer from
+    /// the eye cells to the motor outputs. Empty unless the bird was picked
+    /// by [`Simulation::select`] before that step.
+    pub fn activity(&self) -> &[Vec<f32>] {
+        &self.activity
+    }
 }
@@ -23,11 +23,14 @@ impl ga::Individual for AnimalIndividual {
 }
 
 impl AnimalIndividual {
-    pub fn from_animal(animal: &Animal) -> Self {
-        Self { fitness: animal.satiation as f32, chromosome: animal.as_chromosome() }
+    pub fn from_animal(animal: &Animal) -> Option<Self> {
+        Some(Self {
+            fitness: animal.satiation as f32,
+            chromosome: animal.as_chromosome()?,
+        })
     }
 
-    pub fn into_animal(self, rng: &mut dyn RngCore) -> Animal {
-        Animal::from_chromosome(self.chromosome, rng)
+    pub fn into_animal(self, parent: &dyn Controller, rng: &mut dyn RngCore) -> Option<Animal> {
+        Animal::from_chromosome(self.chromosome, parent, rng)
     }
 }
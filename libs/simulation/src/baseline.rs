@@ -0,0 +1,104 @@
+//! Hand-written controllers that give evolved brains something to be
+//! measured against.
+
+use std::f32::consts::PI;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::*;
+
+/// Wanders by picking both motor outputs uniformly at random every step.
+#[derive(Debug)]
+pub struct RandomWalk {
+    rng: StdRng,
+}
+
+impl RandomWalk {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Controller for RandomWalk {
+    fn observe(
+        &mut self,
+        _observation: &Observation,
+        _policy: nn::NonFinitePolicy,
+    ) -> Result<Vec<f32>, nn::NetworkError> {
+        Ok(vec![
+            self.rng.gen_range(-1.0..=1.0),
+            self.rng.gen_range(-1.0..=1.0),
+        ])
+    }
+}
+
+/// Speeds up and turns toward the middle of the eye cell seeing the closest
+/// food, and coasts straight on when nothing is in sight.
+#[derive(Debug)]
+pub struct StrongestCell {
+    fov_angle: f32,
+}
+
+impl StrongestCell {
+    /// `eye` must be the eye the vision comes from.
+    pub fn new(eye: &Eye) -> Self {
+        Self {
+            fov_angle: eye.fov_angle(),
+        }
+    }
+}
+
+impl Controller for StrongestCell {
+    fn observe(
+        &mut self,
+        observation: &Observation,
+        _policy: nn::NonFinitePolicy,
+    ) -> Result<Vec<f32>, nn::NetworkError> {
+        let vision = observation.vision;
+        let strongest = vision
+            .iter()
+            .enumerate()
+            .filter(|(_, &energy)| energy > 0.0)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        Ok(match strongest {
+            Some((cell, _)) => {
+                let width = self.fov_angle / vision.len() as f32;
+                vec![1.0, -self.fov_angle / 2.0 + (cell as f32 + 0.5) * width]
+            }
+            None => vec![0.0, 0.0],
+        })
+    }
+}
+
+/// Knows where every food is, eye or not, and heads at full speed for the
+/// nearest one, across the world's wrapping edges if that is shorter.
+/// Birds chasing the same food soon bunch up and share it, so this is no
+/// upper bound on fitness.
+#[derive(Debug, Default)]
+pub struct GreedyNearestFood;
+
+impl Controller for GreedyNearestFood {
+    fn observe(
+        &mut self,
+        observation: &Observation,
+        _policy: nn::NonFinitePolicy,
+    ) -> Result<Vec<f32>, nn::NetworkError> {
+        let position = observation.position;
+        let nearest = observation
+            .foods
+            .iter()
+            .map(|food| (food.position - position).map(|axis| na::wrap(axis, -0.5, 0.5)))
+            .min_by(|a, b| a.norm_squared().total_cmp(&b.norm_squared()));
+
+        Ok(match nearest {
+            Some(offset) => {
+                let heading = offset.y.atan2(offset.x) - observation.rotation.angle();
+                vec![1.0, na::wrap(heading, -PI, PI)]
+            }
+            None => vec![0.0, 0.0],
+        })
+    }
+}
@@ -0,0 +1,221 @@
+//! Food eaten by birds all driven by one kind of [`Controller`] over a
+//! generation in a seeded world, so evolved fitness can be quoted as a
+//! fraction of what a known [`Baseline`] manages in the same world.
+
+use std::fmt::Display;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::baseline::{GreedyNearestFood, RandomWalk, StrongestCell};
+use crate::*;
+
+/// One of the hand-written controllers in [`crate::baseline`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Baseline {
+    RandomWalk,
+    StrongestCell,
+    GreedyNearestFood,
+}
+
+impl Baseline {
+    pub const ALL: [Baseline; 3] = [
+        Baseline::RandomWalk,
+        Baseline::StrongestCell,
+        Baseline::GreedyNearestFood,
+    ];
+
+    /// Controller of bird `index` in a world seeded by `seed`.
+    fn controller(self, seed: u64, index: usize) -> Box<dyn Controller> {
+        match self {
+            Baseline::RandomWalk => Box::new(RandomWalk::new(seed.wrapping_add(index as u64))),
+            Baseline::StrongestCell => Box::new(StrongestCell::new(&Eye::default())),
+            Baseline::GreedyNearestFood => Box::new(GreedyNearestFood),
+        }
+    }
+}
+
+impl Display for Baseline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Baseline::RandomWalk => "random walk",
+            Baseline::StrongestCell => "strongest eye cell",
+            Baseline::GreedyNearestFood => "greedy nearest food",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkReport {
+    pub controller: String,
+    pub birds: usize,
+    pub steps: usize,
+    pub food_eaten: usize,
+}
+
+impl BenchmarkReport {
+    /// Food eaten per bird, on the same scale as
+    /// [`Simulation::average_fitness`] after as many steps.
+    pub fn average_fitness(&self) -> f32 {
+        self.food_eaten as f32 / self.birds as f32
+    }
+
+    /// `fitness` as a fraction of this report's average fitness, `None` if
+    /// no bird ate anything.
+    pub fn fraction(&self, fitness: f32) -> Option<f32> {
+        let average = self.average_fitness();
+        (average > 0.0).then(|| fitness / average)
+    }
+}
+
+impl Display for BenchmarkReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} food eaten by {} birds in {} steps ({:.2} per bird)",
+            self.controller,
+            self.food_eaten,
+            self.birds,
+            self.steps,
+            self.average_fitness()
+        )
+    }
+}
+
+/// The benchmark world never evolves, so there is no fitness to observe.
+pub(crate) struct Unobserved;
+
+impl Observer<f32> for Unobserved {
+    fn set(&mut self, _t: f32) -> bool {
+        false
+    }
+
+    fn get(&self) -> f32 {
+        0.0
+    }
+}
+
+/// Runs `steps` steps of the world [`Simulation::random`] builds from `seed`,
+/// with bird `index` driven by `controller(index)`. The same seed gives the
+/// same birds and food, which only drift apart once birds start eating.
+pub fn run(
+    seed: u64,
+    steps: usize,
+    name: impl Into<String>,
+    mut controller: impl FnMut(usize) -> Box<dyn Controller>,
+) -> BenchmarkReport {
+    let (mut rng, mut simulation) = world(seed, steps);
+    for (index, animal) in simulation.world.animals.iter_mut().enumerate() {
+        animal.brain = controller(index);
+    }
+
+    for _ in 0..steps {
+        simulation.step(&mut rng);
+    }
+
+    report(name, steps, &simulation)
+}
+
+/// [`run`] with every bird driven by `network`, propagated for all of them
+/// at once with [`nn::Network::propagate_batch`]. Layers that remember or
+/// learn are left as they are, so only feed-forward networks give the same
+/// report as [`run`] with a copy of `network` per bird.
+pub fn run_shared(
+    seed: u64,
+    steps: usize,
+    name: impl Into<String>,
+    network: &nn::Network,
+) -> BenchmarkReport {
+    let (mut rng, mut simulation) = world(seed, steps);
+
+    for _ in 0..steps {
+        simulation.process_collisions(&mut rng);
+        simulation.process_shared_brain(network);
+        simulation.process_movements();
+    }
+
+    report(name, steps, &simulation)
+}
+
+/// The seeded world, set to last `steps` steps without evolving.
+fn world(seed: u64, steps: usize) -> (StdRng, Simulation) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut simulation = Simulation::random(&mut rng, Box::new(Unobserved));
+    simulation.generation_length = steps;
+    (rng, simulation)
+}
+
+fn report(name: impl Into<String>, steps: usize, simulation: &Simulation) -> BenchmarkReport {
+    let animals = &simulation.world.animals;
+    BenchmarkReport {
+        controller: name.into(),
+        birds: animals.len(),
+        steps,
+        food_eaten: animals.iter().map(|animal| animal.satiation).sum(),
+    }
+}
+
+/// [`run`] for every [`Baseline`].
+pub fn baselines(seed: u64, steps: usize) -> Vec<BenchmarkReport> {
+    Baseline::ALL
+        .iter()
+        .map(|&baseline| {
+            run(seed, steps, baseline.to_string(), |index| {
+                baseline.controller(seed, index)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 7;
+    const STEPS: usize = 200;
+
+    fn report(baseline: Baseline) -> BenchmarkReport {
+        run(SEED, STEPS, baseline.to_string(), |index| {
+            baseline.controller(SEED, index)
+        })
+    }
+
+    #[test]
+    fn test_same_seed_same_report() {
+        assert_eq!(report(Baseline::RandomWalk), report(Baseline::RandomWalk));
+        assert_eq!(baselines(SEED, STEPS), Baseline::ALL.map(report).to_vec());
+    }
+
+    #[test]
+    fn test_greedy_beats_random_walk() {
+        let random = report(Baseline::RandomWalk);
+        let greedy = report(Baseline::GreedyNearestFood);
+        assert!(greedy.food_eaten > random.food_eaten);
+        assert!(random.fraction(greedy.average_fitness()).unwrap() > 1.0);
+    }
+
+    #[test]
+    fn test_shared_network_runs_as_copies() {
+        let mut rng = StdRng::seed_from_u64(SEED);
+        let brain = Brain::random(&mut rng, &Eye::default(), nn::LayerKind::FeedForward);
+        let network = brain.network().unwrap();
+
+        let copies = run(SEED, STEPS, "copies", |_| {
+            let copy = nn::Network::from_weights(network.topology(), network.weights());
+            Box::new(Brain::from_network(copy))
+        });
+        let shared = run_shared(SEED, STEPS, "copies", network);
+        assert_eq!(shared, copies);
+    }
+
+    #[test]
+    fn test_fraction_of_nothing() {
+        let report = BenchmarkReport {
+            controller: "idle".to_string(),
+            birds: 40,
+            steps: 0,
+            food_eaten: 0,
+        };
+        assert_eq!(report.fraction(1.0), None);
+    }
+}
@@ -1,42 +1,202 @@
 use crate::*;
 
 #[derive(Debug)]
-pub struct Brain {
-    pub(crate) nn: nn::Network,
+pub enum Brain {
+    /// Fixed topology network evolved through its chromosome, with the
+    /// workspace it propagates through.
+    Network(nn::Network, nn::Workspace),
+    /// Topology-evolving network bred by a [`nn::neat::Population`].
+    Neat(nn::neat::NeatNetwork),
 }
 
 impl Brain {
-    pub fn random(rng: &mut dyn RngCore, eye: &Eye) -> Self {
-        Self {
-            nn: nn::Network::random(rng, &Self::topology(eye)),
-        }
+    /// `hidden` is the kind of the hidden layer; a
+    /// [`nn::LayerKind::Hebbian`] one keeps adapting by an evolved rule while
+    /// the bird lives, and a recurrent one remembers earlier steps.
+    pub fn random(rng: &mut dyn RngCore, eye: &Eye, hidden: nn::LayerKind) -> Self {
+        Self::from_network(nn::Network::random(rng, &Self::topology(eye, hidden)))
     }
 
-    pub (crate) fn as_chromosome(&self) -> ga::Chromosome {
-        self.nn.weights().collect()
+    pub(crate) fn from_network(network: nn::Network) -> Self {
+        let workspace = network.workspace();
+        Self::Network(network, workspace)
     }
 
-    pub(crate) fn from_chromosome(
-        chromosome: ga::Chromosome,
-        eye: &Eye,
-    ) -> Self {
-        Self {
-            nn: nn::Network::from_weights(
-                &Self::topology(eye),
-                chromosome,
-            ),
-        }
+    pub(crate) fn from_genome(genome: &nn::neat::Genome) -> Self {
+        Self::Neat(genome.network())
     }
 
-    fn topology(eye: &Eye) -> [nn::LayerTopology; 3] {
+    pub(crate) fn neat_config(eye: &Eye) -> nn::neat::NeatConfig {
+        nn::neat::NeatConfig::new(eye.cells(), 2)
+    }
+
+    /// The output layer is signed so a bird can both slow down and turn
+    /// either way. A recurrent hidden layer squashes its state with `tanh` so
+    /// it cannot grow without bound over a generation.
+    fn topology(eye: &Eye, hidden: nn::LayerKind) -> [nn::LayerTopology; 3] {
+        let (activation, initializer) = match hidden {
+            nn::LayerKind::Elman | nn::LayerKind::Gru => {
+                (nn::Activation::Tanh, nn::Initializer::Xavier)
+            }
+            _ => (nn::Activation::ReLU, nn::Initializer::He),
+        };
         [
             nn::LayerTopology {
                 neurons: eye.cells(),
+                activation: nn::Activation::Identity,
+                ..Default::default()
             },
             nn::LayerTopology {
                 neurons: 2 * eye.cells(),
+                activation,
+                kind: hidden,
+                initializer,
+                ..Default::default()
+            },
+            nn::LayerTopology {
+                neurons: 2,
+                activation: nn::Activation::Tanh,
+                initializer: nn::Initializer::Xavier,
+                ..Default::default()
             },
-            nn::LayerTopology { neurons: 2 },
         ]
     }
-}
\ No newline at end of file
+}
+
+impl Controller for Brain {
+    /// A NEAT network is only checked once it has run, and only at its
+    /// outputs.
+    fn observe(
+        &mut self,
+        observation: &Observation,
+        policy: nn::NonFinitePolicy,
+    ) -> Result<Vec<f32>, nn::NetworkError> {
+        match self {
+            Self::Network(nn, workspace) => {
+                let mut response = vec![0.0; 2];
+                nn.try_propagate_into(observation.vision, &mut response, workspace, policy)?;
+                Ok(response)
+            }
+            Self::Neat(nn) => {
+                let mut response = nn.propagate(observation.vision);
+                // a NEAT trace is inputs, hidden nodes and outputs
+                policy.apply(2, &mut response)?;
+                Ok(response)
+            }
+        }
+    }
+
+    /// Activations of every layer, from the vision to the motor outputs. A
+    /// NEAT network is only checked once it has run, so its outputs are
+    /// sanitized but were computed from the raw hidden values.
+    fn observe_traced(
+        &mut self,
+        observation: &Observation,
+        policy: nn::NonFinitePolicy,
+    ) -> Result<Vec<Vec<f32>>, nn::NetworkError> {
+        match self {
+            Self::Network(nn, _) => nn.try_propagate_traced(observation.vision.to_vec(), policy),
+            Self::Neat(nn) => {
+                let mut trace = nn.propagate_traced(observation.vision);
+                for (layer, values) in trace.iter_mut().enumerate() {
+                    policy.apply(layer, values)?;
+                }
+                Ok(trace)
+            }
+        }
+    }
+
+    /// NEAT brains are bred from genomes, not chromosomes.
+    fn encode(&self) -> Option<ga::Chromosome> {
+        match self {
+            Self::Network(nn, _) => Some(nn.weights().collect()),
+            Self::Neat(_) => None,
+        }
+    }
+
+    /// A fresh network, so plastic weights start over from the evolved ones
+    /// rather than carrying what the parents learned.
+    fn decode(&self, chromosome: ga::Chromosome) -> Option<Box<dyn Controller>> {
+        match self {
+            Self::Network(nn, _) => Some(Box::new(Self::from_network(nn::Network::from_weights(
+                nn.topology(),
+                chromosome,
+            )))),
+            Self::Neat(_) => None,
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Self::Network(nn, _) => nn.reset_state(),
+            // NEAT networks are feed-forward and hold no state between steps
+            Self::Neat(_) => {}
+        }
+    }
+
+    fn network(&self) -> Option<&nn::Network> {
+        match self {
+            Self::Network(nn, _) => Some(nn),
+            Self::Neat(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn brain(hidden: nn::LayerKind) -> Brain {
+        Brain::random(&mut StdRng::seed_from_u64(1), &Eye::default(), hidden)
+    }
+
+    fn observation(vision: &[f32]) -> Observation<'_> {
+        Observation {
+            vision,
+            position: na::Point2::new(0.5, 0.5),
+            rotation: na::Rotation2::new(0.0),
+            foods: &[],
+        }
+    }
+
+    /// Motor outputs of a fresh brain seeing the same thing `steps` times,
+    /// then once more after a reset.
+    fn responses(hidden: nn::LayerKind, steps: usize) -> Vec<Vec<f32>> {
+        let mut brain = brain(hidden);
+        let vision = vec![0.5; Eye::default().cells()];
+        let observation = observation(&vision);
+        let observe = |brain: &mut Brain| {
+            let response = brain.observe(&observation, nn::NonFinitePolicy::Error);
+            response.unwrap()
+        };
+
+        let mut responses: Vec<_> = (0..steps).map(|_| observe(&mut brain)).collect();
+        brain.reset();
+        responses.push(observe(&mut brain));
+        responses
+    }
+
+    #[test]
+    fn test_only_recurrent_brains_remember() {
+        let stateless = responses(nn::LayerKind::FeedForward, 2);
+        assert_eq!(stateless[0], stateless[1]);
+
+        let recurrent = responses(nn::LayerKind::Gru, 2);
+        assert_ne!(recurrent[0], recurrent[1]);
+        assert_eq!(recurrent[0], recurrent[2]);
+    }
+
+    #[test]
+    fn test_traced_observation_ends_with_the_response() {
+        let vision = vec![0.5; Eye::default().cells()];
+        let policy = nn::NonFinitePolicy::Error;
+        let response = brain(nn::LayerKind::FeedForward).observe(&observation(&vision), policy);
+        let trace = brain(nn::LayerKind::FeedForward).observe_traced(&observation(&vision), policy);
+
+        let trace = trace.unwrap();
+        assert_eq!(trace.len(), 3);
+        assert_eq!(trace[0], vision);
+        assert_eq!(trace.last(), response.ok().as_ref());
+    }
+}
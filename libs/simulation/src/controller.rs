@@ -0,0 +1,59 @@
+use std::fmt::Debug;
+
+use crate::*;
+
+/// Everything a bird's controller may base a step on. Evolved brains only
+/// look at `vision`; the rest is there for hand-written controllers.
+#[derive(Clone, Copy, Debug)]
+pub struct Observation<'a> {
+    /// What the bird's [`Eye`] sees, one value per cell.
+    pub vision: &'a [f32],
+    pub position: na::Point2<f32>,
+    pub rotation: na::Rotation2<f32>,
+    pub foods: &'a [Food],
+}
+
+/// Whatever steers a bird: an evolved [`nn::Network`], a NEAT network or a
+/// hand-written rule.
+pub trait Controller: Debug {
+    /// Motor outputs `[acceleration, rotation]` for this step. Network
+    /// controllers handle `NaN` and infinities by `policy`.
+    fn observe(
+        &mut self,
+        observation: &Observation,
+        policy: nn::NonFinitePolicy,
+    ) -> Result<Vec<f32>, nn::NetworkError>;
+
+    /// Same as [`Controller::observe`], but returns the activations behind the
+    /// decision, ending with the motor outputs; they become
+    /// [`Animal::activity`] of the selected bird. Only the vision and the
+    /// motor outputs unless the controller has layers in between.
+    fn observe_traced(
+        &mut self,
+        observation: &Observation,
+        policy: nn::NonFinitePolicy,
+    ) -> Result<Vec<Vec<f32>>, nn::NetworkError> {
+        let response = self.observe(observation, policy)?;
+        Ok(vec![observation.vision.to_vec(), response])
+    }
+
+    /// Genes the genetic algorithm breeds, `None` for controllers that are
+    /// not bred from chromosomes.
+    fn encode(&self) -> Option<ga::Chromosome> {
+        None
+    }
+
+    /// A new controller shaped like this one, holding `chromosome` as encoded
+    /// by [`Controller::encode`].
+    fn decode(&self, _chromosome: ga::Chromosome) -> Option<Box<dyn Controller>> {
+        None
+    }
+
+    /// Forgets whatever the controller picked up while running.
+    fn reset(&mut self) {}
+
+    /// The fixed topology network behind this controller, if there is one.
+    fn network(&self) -> Option<&nn::Network> {
+        None
+    }
+}
@@ -31,6 +31,11 @@ impl Eye {
         self.cells
     }
 
+    /// Angle the cells split between them, centered on the heading.
+    pub fn fov_angle(&self) -> f32 {
+        self.fov_angle
+    }
+
     pub fn process_vision(
         &self,
         position: na::Point2<f32>,
@@ -1,5 +1,5 @@
 use animal_individual::AnimalIndividual;
-use log::info;
+use log::{info, warn};
 use nalgebra as na;
 use rand::{Rng, RngCore};
 use std::f32::consts::FRAC_PI_2;
@@ -14,7 +14,10 @@ pub trait Observer<T: Clone> {
 
 pub mod animal;
 mod animal_individual;
+pub mod baseline;
+pub mod benchmark;
 mod brain;
+pub mod controller;
 pub mod eye;
 pub mod food;
 pub mod world;
@@ -25,7 +28,7 @@ use ga::{
 };
 use lib_genetic_algorithm as ga;
 use lib_neural_network as nn;
-use {animal::*, brain::*, eye::*, food::*, world::*};
+use {animal::*, brain::*, controller::*, eye::*, food::*, world::*};
 
 const SPEED_MIN: f32 = 0.001;
 const SPEED_MAX: f32 = 0.005;
@@ -37,6 +40,18 @@ pub struct Simulation {
     world: World,
     genetic_algorithm:
         ga::GeneticAlgorithm<RouletteWheelSelection, UniformCrossover, GaussianMutation>,
+    /// Set when the brains are NEAT networks bred by this population instead
+    /// of fixed networks bred by `genetic_algorithm`.
+    neat: Option<nn::neat::Population>,
+    /// How brains that output `NaN` or an infinity are handled each step,
+    /// [`nn::NonFinitePolicy::default`] to begin with. With
+    /// [`nn::NonFinitePolicy::Error`] the bird neither accelerates nor turns
+    /// for that step and its controller is reset; see [`Simulation::stalls`].
+    pub non_finite_policy: nn::NonFinitePolicy,
+    /// Steps this generation on which a controller failed.
+    stalls: usize,
+    /// Index of the bird whose [`Animal::activity`] is recorded.
+    selected: Option<usize>,
     pub age: usize,
     pub generation_length: usize,
     pub fitness_observer: Box<dyn Observer<f32>>,
@@ -47,6 +62,15 @@ const EPSILON: f32 = 0.01;
 impl Simulation {
     pub fn random(rng: &mut dyn RngCore, fitness_observer: Box<dyn Observer<f32>>) -> Self {
         info!("new random simulation");
+        Self::new(rng, fitness_observer, nn::LayerKind::FeedForward)
+    }
+
+    fn new(
+        rng: &mut dyn RngCore,
+        fitness_observer: Box<dyn Observer<f32>>,
+        hidden: nn::LayerKind,
+    ) -> Self {
+        #[allow(clippy::default_constructed_unit_structs)]
         let ga = ga::GeneticAlgorithm::new(
             RouletteWheelSelection::default(),
             UniformCrossover::default(),
@@ -54,18 +78,87 @@ impl Simulation {
         );
 
         Self {
-            world: World::random(rng),
+            world: World::random(rng, hidden),
             genetic_algorithm: ga,
+            neat: None,
+            non_finite_policy: nn::NonFinitePolicy::default(),
+            stalls: 0,
+            selected: None,
             age: 0,
             generation_length: GENERATION_LENGTH,
             fitness_observer,
         }
     }
 
+    /// Like [`Simulation::random`], but the birds carry NEAT brains whose
+    /// topology evolves alongside their weights.
+    pub fn random_neat(rng: &mut dyn RngCore, fitness_observer: Box<dyn Observer<f32>>) -> Self {
+        info!("new random NEAT simulation");
+        let mut simulation = Self::random(rng, fitness_observer);
+
+        let config = Brain::neat_config(&Eye::default());
+        let population = nn::neat::Population::new(config, simulation.world.animals.len(), rng);
+        simulation.world.animals = population
+            .genomes()
+            .iter()
+            .map(|genome| Animal::from_genome(genome, rng))
+            .collect();
+        simulation.neat = Some(population);
+
+        simulation
+    }
+
+    /// Like [`Simulation::random`], but the hidden layer of every brain keeps
+    /// learning by a Hebbian rule during a generation. The rule is evolved
+    /// along with the weights; what a bird learns is not passed on.
+    pub fn random_plastic(rng: &mut dyn RngCore, fitness_observer: Box<dyn Observer<f32>>) -> Self {
+        info!("new random plastic simulation");
+        Self::new(rng, fitness_observer, nn::LayerKind::Hebbian)
+    }
+
+    /// Like [`Simulation::random`], but the hidden layer of every brain is a
+    /// gated recurrent unit, so a bird can remember food it has turned away
+    /// from. The memory is cleared for every new generation.
+    pub fn random_recurrent(
+        rng: &mut dyn RngCore,
+        fitness_observer: Box<dyn Observer<f32>>,
+    ) -> Self {
+        info!("new random recurrent simulation");
+        Self::new(rng, fitness_observer, nn::LayerKind::Gru)
+    }
+
     pub fn world(&self) -> &World {
         &self.world
     }
 
+    /// How many of the best fed birds pass their chromosome on unchanged.
+    /// NEAT simulations breed through their own population and ignore it.
+    pub fn elitism(&self) -> ga::Elitism {
+        self.genetic_algorithm.elitism()
+    }
+
+    pub fn set_elitism(&mut self, elitism: ga::Elitism) {
+        self.genetic_algorithm.set_elitism(elitism);
+    }
+
+    /// Records the [`Animal::activity`] of bird `index` from the next step
+    /// on, or of no bird. Tracing costs an allocation per layer, so the other
+    /// birds are never traced.
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
+    }
+
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// How many times a bird's controller failed and left the bird coasting
+    /// so far this generation. The first failure of a generation is logged
+    /// as it happens, the total when the generation ends.
+    pub fn stalls(&self) -> usize {
+        self.stalls
+    }
+
     // TODO: Kill animals
     // TODO: Mate birds
     pub fn step(&mut self, rng: &mut dyn RngCore) {
@@ -84,53 +177,252 @@ impl Simulation {
         self.world
             .animals
             .iter()
-            .map(AnimalIndividual::from_animal)
-            .map(|it| it.fitness)
+            .map(|animal| animal.satiation as f32)
             .sum::<f32>()
             .div(self.world.animals.len() as f32)
     }
 
+    /// Quantizes the brain of the best fed bird to int8 and measures how far
+    /// it drifts from the `f32` brain on what every bird saw last step.
+    /// `None` for NEAT and plastic simulations and before the first step.
+    pub fn quantization_report(&self) -> Option<nn::quantize::AccuracyReport> {
+        let vision: Vec<_> = self
+            .world
+            .animals
+            .iter()
+            .filter(|animal| !animal.vision.is_empty())
+            .map(|animal| animal.vision.clone())
+            .collect();
+        if vision.is_empty() {
+            return None;
+        }
+
+        let network = self.champion_network()?;
+        let quantized = nn::QuantizedNetwork::new(network, &vision).ok()?;
+        quantized.compare(network, &vision).ok()
+    }
+
+    /// SVG diagram of the best fed bird's brain, for experiment reports.
+    /// `None` for NEAT simulations.
+    pub fn champion_svg(&self) -> Option<String> {
+        self.champion_network()?.to_svg().ok()
+    }
+
+    /// Runs fresh copies of this generation's brains for `steps` steps in the
+    /// world seeded by `seed`, to be compared with
+    /// [`benchmark::baselines`] for the same seed. `None` if the birds are
+    /// driven by controllers that cannot be copied, such as the [`baseline`]
+    /// ones.
+    pub fn benchmark(&self, seed: u64, steps: usize) -> Option<benchmark::BenchmarkReport> {
+        let copies: Option<Vec<Box<dyn Controller>>> = match &self.neat {
+            Some(population) => Some(
+                population
+                    .genomes()
+                    .iter()
+                    .map(|genome| Box::new(Brain::from_genome(genome)) as Box<dyn Controller>)
+                    .collect(),
+            ),
+            None => self
+                .world
+                .animals
+                .iter()
+                .map(|animal| {
+                    let brain = &animal.brain;
+                    brain
+                        .encode()
+                        .and_then(|chromosome| brain.decode(chromosome))
+                })
+                .collect(),
+        };
+
+        // the benchmark world has as many birds as this one, built in order
+        let mut copies = copies?.into_iter();
+        Some(benchmark::run(seed, steps, "evolved", |_| {
+            copies.next().expect("one copy per bird")
+        }))
+    }
+
+    /// Runs the best fed bird's brain in every bird of the world seeded by
+    /// `seed` for `steps` steps. The birds share one network, so each step
+    /// is a single [`nn::Network::propagate_batch`] call. `None` for NEAT
+    /// simulations and for brains with layers that remember or learn, which
+    /// a batch would leave unchanged.
+    pub fn benchmark_champion(
+        &self,
+        seed: u64,
+        steps: usize,
+    ) -> Option<benchmark::BenchmarkReport> {
+        let network = self.champion_network()?;
+        let stateless = network.topology().iter().all(|layer| {
+            matches!(
+                layer.kind,
+                nn::LayerKind::FeedForward | nn::LayerKind::Conv1d { .. }
+            )
+        });
+        stateless.then(|| benchmark::run_shared(seed, steps, "champion", network))
+    }
+
+    /// Brain of the best fed bird, unless brains are NEAT networks.
+    fn champion_network(&self) -> Option<&nn::Network> {
+        self.world
+            .animals
+            .iter()
+            .max_by_key(|animal| animal.satiation)?
+            .brain
+            .network()
+    }
+
     fn evolve(&mut self, rng: &mut dyn RngCore) {
         info!("stepping forward a generation");
+        if self.stalls > 0 {
+            warn!("{} bird brain stalls this generation", self.stalls);
+        }
         self.age = 0;
+        self.stalls = 0;
         self.fitness_observer.set(self.average_fitness());
 
-        let current_population: Vec<_> = self
+        if let Some(population) = &mut self.neat {
+            let fitnesses: Vec<_> = self
+                .world
+                .animals
+                .iter()
+                .map(|animal| animal.satiation as f32)
+                .collect();
+            population.evolve(rng, &fitnesses);
+
+            self.world.animals = population
+                .genomes()
+                .iter()
+                .map(|genome| Animal::from_genome(genome, rng))
+                .collect();
+        } else {
+            self.evolve_chromosomes(rng);
+        }
+
+        for food in &mut self.world.foods {
+            food.position = rng.gen();
+        }
+    }
+
+    /// Birds whose controllers are not bred from chromosomes, such as the
+    /// [`baseline`] ones, are kept and start the next generation hungry.
+    fn evolve_chromosomes(&mut self, rng: &mut dyn RngCore) {
+        let current_population: Option<Vec<_>> = self
             .world
             .animals
             .iter()
             .map(AnimalIndividual::from_animal)
             .collect();
 
-        let evolved_population = self.genetic_algorithm.evolve(rng, &current_population);
+        // every bird of a generation has the same kind of controller
+        let parent = &*self.world.animals[0].brain;
+        let genetic_algorithm = &self.genetic_algorithm;
+        let evolved_animals = current_population.and_then(|current_population| {
+            genetic_algorithm
+                .evolve(rng, &current_population)
+                .into_iter()
+                .map(|animal_individual| animal_individual.into_animal(parent, rng))
+                .collect::<Option<Vec<_>>>()
+        });
 
-        self.world.animals = evolved_population
-            .into_iter()
-            .map(|animal_individual| animal_individual.into_animal(rng))
-            .collect();
-
-        for food in &mut self.world.foods {
-            food.position = rng.gen();
+        match evolved_animals {
+            Some(animals) => self.world.animals = animals,
+            None => {
+                info!("controllers are not bred from chromosomes, keeping them");
+                for animal in &mut self.world.animals {
+                    animal.satiation = 0;
+                    animal.brain.reset();
+                }
+            }
         }
     }
 
     fn process_brains(&mut self) {
-        for animal in &mut self.world.animals {
+        for (index, animal) in self.world.animals.iter_mut().enumerate() {
             let vision =
                 animal
                     .eye
                     .process_vision(animal.position, animal.rotation, &self.world.foods);
-            let response = animal.see(vision);
+            let observation = Observation {
+                vision: &vision,
+                position: animal.position,
+                rotation: animal.rotation,
+                foods: &self.world.foods,
+            };
+            let traced = self.selected == Some(index);
+            let response = match animal.see(&observation, self.non_finite_policy, traced) {
+                Ok(response) => response,
+                Err(err) => {
+                    Self::stall(&mut self.stalls, &err);
+                    animal.brain.reset();
+                    vec![0.0; 2]
+                }
+            };
+            animal.vision = vision;
 
-            let speed = response[0].clamp(-SPEED_ACCEL, SPEED_ACCEL);
+            Self::steer(animal, &response);
+        }
+    }
 
-            let rotation = response[1].clamp(-ROTATION_ACCEL, ROTATION_ACCEL);
+    /// Like [`Simulation::process_brains`], but every bird is driven by
+    /// `network` instead of its own controller, all of them in one
+    /// [`nn::Network::propagate_batch`] call. Only the outputs are checked
+    /// against the non-finite policy, and no bird is traced.
+    fn process_shared_brain(&mut self, network: &nn::Network) {
+        let foods = &self.world.foods;
+        let visions: Vec<_> = self
+            .world
+            .animals
+            .iter()
+            .map(|animal| {
+                animal
+                    .eye
+                    .process_vision(animal.position, animal.rotation, foods)
+            })
+            .collect();
+        let inputs =
+            na::DMatrix::from_fn(network.topology()[0].neurons, visions.len(), |row, col| {
+                visions[col][row]
+            });
+        let outputs = network.propagate_batch(&inputs);
+        let output_layer = network.topology().len() - 1;
 
-            animal.speed = (animal.speed + speed).clamp(SPEED_MIN, SPEED_MAX);
-            animal.rotation = na::Rotation2::new(animal.rotation.angle() + rotation);
+        for ((animal, vision), output) in self
+            .world
+            .animals
+            .iter_mut()
+            .zip(visions)
+            .zip(outputs.column_iter())
+        {
+            let mut response: Vec<f32> = output.iter().copied().collect();
+            if let Err(err) = self.non_finite_policy.apply(output_layer, &mut response) {
+                Self::stall(&mut self.stalls, &err);
+                response = vec![0.0; 2];
+            }
+            animal.vision = vision;
+
+            Self::steer(animal, &response);
         }
     }
 
+    /// Counts a failed controller; only the first failure of a generation is
+    /// logged.
+    fn stall(stalls: &mut usize, err: &nn::NetworkError) {
+        if *stalls == 0 {
+            warn!("bird brain stalled: {}", err);
+        }
+        *stalls += 1;
+    }
+
+    fn steer(animal: &mut Animal, response: &[f32]) {
+        let speed = response[0].clamp(-SPEED_ACCEL, SPEED_ACCEL);
+
+        let rotation = response[1].clamp(-ROTATION_ACCEL, ROTATION_ACCEL);
+
+        animal.speed = (animal.speed + speed).clamp(SPEED_MIN, SPEED_MAX);
+        animal.rotation = na::Rotation2::new(animal.rotation.angle() + rotation);
+    }
+
     fn process_movements(&mut self) {
         for animal in &mut self.world.animals {
             animal.position += animal.rotation * na::Vector2::new(animal.speed, 0.0);
@@ -153,3 +445,103 @@ impl Simulation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::GreedyNearestFood;
+    use crate::benchmark::Unobserved;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Fails every step, like a brain whose output overflows.
+    #[derive(Debug)]
+    struct Broken;
+
+    impl Controller for Broken {
+        fn observe(
+            &mut self,
+            _observation: &Observation,
+            _policy: nn::NonFinitePolicy,
+        ) -> Result<Vec<f32>, nn::NetworkError> {
+            Err(nn::NetworkError::NonFiniteActivation {
+                layer: 2,
+                neuron: 0,
+                value: f64::NAN,
+            })
+        }
+    }
+
+    /// A short-lived generation with every bird driven by `controller()`.
+    fn simulation(rng: &mut StdRng, controller: fn() -> Box<dyn Controller>) -> Simulation {
+        let mut simulation = Simulation::random(rng, Box::new(Unobserved));
+        simulation.generation_length = 10;
+        for animal in &mut simulation.world.animals {
+            animal.brain = controller();
+        }
+        simulation
+    }
+
+    #[test]
+    fn test_hand_written_controllers_outlive_their_generation() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut simulation = simulation(&mut rng, || Box::new(GreedyNearestFood));
+        simulation.select(Some(0));
+
+        for _ in 0..simulation.generation_length {
+            simulation.step(&mut rng);
+        }
+        let animal = &simulation.world.animals[0];
+        assert_eq!(animal.activity().len(), 2);
+        assert_eq!(animal.activity()[0], animal.vision);
+
+        // one more step ends the generation
+        simulation.world.animals[0].satiation = 5;
+        simulation.step(&mut rng);
+        assert_eq!(simulation.age, 0);
+        assert_eq!(simulation.world.animals.len(), 40);
+        assert!(simulation
+            .world
+            .animals
+            .iter()
+            .all(|animal| animal.satiation == 0));
+        assert!(simulation
+            .world
+            .animals
+            .iter()
+            .all(|animal| animal.brain.encode().is_none()));
+
+        simulation.step(&mut rng);
+        assert_eq!(simulation.age, 1);
+    }
+
+    #[test]
+    fn test_benchmark_copies_bred_brains_only() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let evolved = Simulation::random(&mut rng, Box::new(Unobserved));
+        let report = evolved.benchmark(5, 10).unwrap();
+        assert_eq!(report.birds, 40);
+        assert_eq!(report.steps, 10);
+
+        let greedy = simulation(&mut rng, || Box::new(GreedyNearestFood));
+        assert_eq!(greedy.benchmark(5, 10), None);
+    }
+
+    #[test]
+    fn test_failing_controllers_stall() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut simulation = simulation(&mut rng, || Box::new(Broken));
+        let speeds: Vec<_> = simulation.world.animals.iter().map(|a| a.speed).collect();
+
+        simulation.step(&mut rng);
+        assert_eq!(simulation.stalls(), 40);
+        for (animal, speed) in simulation.world.animals.iter().zip(speeds) {
+            assert_eq!(animal.speed, speed);
+        }
+
+        for _ in 0..simulation.generation_length {
+            simulation.step(&mut rng);
+        }
+        assert_eq!(simulation.age, 0);
+        assert_eq!(simulation.stalls(), 0);
+    }
+}
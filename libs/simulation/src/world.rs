@@ -10,8 +10,9 @@ pub struct World {
 // TODO: Add parameters for the number of animals and foods
 // TODO: 100% coverage
 impl World {
-    pub fn random(rng: &mut dyn RngCore) -> Self {
-        let animals = (0..40).map(|_| Animal::random(rng)).collect();
+    /// Every bird gets a random brain whose hidden layer is of kind `hidden`.
+    pub fn random(rng: &mut dyn RngCore, hidden: nn::LayerKind) -> Self {
+        let animals = (0..40).map(|_| Animal::random(rng, hidden)).collect();
 
         let foods = (0..60).map(|_| Food::random(rng)).collect();
         Self { animals, foods }